@@ -0,0 +1,425 @@
+use crate::doctor;
+use crate::error::InitError;
+use crate::spec::{Bundler, Framework, Language, PackageManager, ProjectSpec, WasmMode};
+
+pub const USAGE: &str = "Usage:
+    init_web_app [new] [NAME] [OPTIONS]
+    init_web_app doctor [--type <TYPE>] [--framework <NAME>] [--wasm <MODE>]
+                        [--language <LANG>] [--bundler <NAME>]
+                        [--package-manager <NAME>] [--offline] [--server]
+                        [--workspace]
+    init_web_app serve [PROJECT_DIR] [--port <PORT>] [--no-reload]
+    init_web_app watch [PROJECT_DIR]
+    init_web_app help
+
+Commands:
+    new      create a project (default)
+    doctor   check that the tools a project type needs are installed;
+             checks every tool when no type is given
+    serve    serve a generated project's pkg directory on localhost
+             (default port: 8080), reloading open pages when pkg changes;
+             wasm threads projects are served cross-origin isolated
+    watch    rerun the script, wasm-pack or Trunk build of a generated
+             project whenever its JSX or Rust sources change
+
+Options:
+    -t, --type <TYPE>    preset combining --framework and --wasm, one of:
+                             vanilla-js
+                             vanilla-js-wasm
+                             vanilla-js-wasm-worker
+                             react
+                             react-wasm
+                             react-wasm-worker
+    --framework <NAME>   vanilla or react, or yew, leptos or dioxus for a UI
+                         written entirely in Rust and built with Trunk
+    --wasm <MODE>        none, main, worker, pool or threads
+    --workers <N>        number of workers in a pool (default: one per core)
+    --module-workers     run workers as ES modules on the same wasm-pack
+                         --target web output as the page
+    --server             add an axum crate in server/ serving pkg/ and a
+                         JSON API the page fetches, in a Cargo workspace
+                         with the wasm crate
+    --workspace          lay the Rust crates out as a Cargo workspace:
+                         crates/wasm, crates/shared with the types the wasm
+                         crate and server share, and crates/server
+    --language <LANG>    js or ts; only asked for on a terminal, otherwise js.
+                         With ts the worker script of a --wasm worker, pool
+                         or threads project stays JavaScript, written
+                         straight into pkg/
+    --typescript         same as --language ts
+    --bundler <NAME>     none, vite or esbuild; only asked for on a
+                         terminal, otherwise none
+    --package-manager <NAME>
+                         npm, pnpm, yarn or bun; defaults to
+                         $INIT_WEB_APP_PACKAGE_MANAGER, else the one this
+                         was launched through, else the first of pnpm,
+                         yarn and bun that is installed, else npm
+    --offline            generate without network access: React is copied
+                         into pkg/vendor instead of loaded from unpkg, and
+                         packages and crates come from the local caches;
+                         with yarn this needs Yarn 1
+    --registry <URL>     npm registry to install from, such as a local
+                         mirror; written to the project's .npmrc, or to
+                         .yarnrc.yml for Yarn 2 and later
+    -p, --path <DIR>     directory to create the project in (default: .)
+    -a, --answers <FILE> read answers to prompts from FILE, one per line
+    --dry-run            print the files and commands that would be created
+                         and run, without writing anything
+    -h, --help           print this message
+
+Anything not supplied on the command line is prompted for. When stdin is
+not a terminal, answers are read from it line by line instead.
+
+Exit codes:
+    0    success
+    2    invalid arguments
+    3    no usable answer to a prompt
+    4    project directory already exists
+    5    file system error
+    6    external tool could not be started
+    7    external tool failed
+    8    required tools are missing
+    130  interrupted with Ctrl-C";
+
+#[derive(Debug)]
+pub enum SubCommand {
+    New(NewArgs),
+    Doctor(DoctorArgs),
+    Serve(ServeArgs),
+    Watch(String),
+    Help,
+}
+
+#[derive(Debug, Default)]
+pub struct NewArgs {
+    pub name: Option<String>,
+    pub framework: Option<Framework>,
+    pub wasm: Option<WasmMode>,
+    pub language: Option<Language>,
+    pub bundler: Option<Option<Bundler>>,
+    pub package_manager: Option<PackageManager>,
+    pub offline: bool,
+    pub registry: Option<String>,
+    pub workers: Option<u32>,
+    pub module_workers: bool,
+    pub server: bool,
+    pub workspace: bool,
+    pub path: Option<String>,
+    pub answers: Option<String>,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct DoctorArgs {
+    /// Project to check tools for, with unset options at their defaults;
+    /// `None` when no options were given.
+    pub spec: Option<ProjectSpec>,
+}
+
+#[derive(Debug)]
+pub struct ServeArgs {
+    pub dir: String,
+    pub port: u16,
+    pub live_reload: bool,
+}
+
+pub fn parse<I: Iterator<Item = String>>(args: I) -> Result<SubCommand, InitError> {
+    let mut args = args.peekable();
+    match args.peek().map(String::as_str) {
+        Some("-h" | "--help" | "help") => Ok(SubCommand::Help),
+        Some("doctor") => {
+            args.next();
+            parse_doctor(args)
+        }
+        Some("serve") => {
+            args.next();
+            parse_serve(args)
+        }
+        Some("watch") => {
+            args.next();
+            parse_watch(args)
+        }
+        Some("new") => {
+            args.next();
+            parse_new(args)
+        }
+        _ => parse_new(args),
+    }
+}
+
+fn parse_new<I: Iterator<Item = String>>(mut args: I) -> Result<SubCommand, InitError> {
+    let mut new_args = NewArgs::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(SubCommand::Help),
+            "-t" | "--type" => {
+                let spec = ProjectSpec::from_preset(&value_for(&arg, args.next())?)?;
+                new_args.framework = Some(spec.framework);
+                new_args.wasm = Some(spec.wasm);
+            }
+            "--framework" => {
+                new_args.framework = Some(Framework::from_slug(&value_for(&arg, args.next())?)?)
+            }
+            "--wasm" => new_args.wasm = Some(WasmMode::from_slug(&value_for(&arg, args.next())?)?),
+            "--language" => {
+                new_args.language = Some(Language::from_slug(&value_for(&arg, args.next())?)?)
+            }
+            "--typescript" => new_args.language = Some(Language::TypeScript),
+            "--bundler" => {
+                new_args.bundler = Some(Bundler::from_slug(&value_for(&arg, args.next())?)?)
+            }
+            "--package-manager" => {
+                new_args.package_manager =
+                    Some(PackageManager::from_slug(&value_for(&arg, args.next())?)?)
+            }
+            "--offline" => new_args.offline = true,
+            "--registry" => new_args.registry = Some(value_for(&arg, args.next())?),
+            "--workers" => {
+                let value = value_for(&arg, args.next())?;
+                new_args.workers = match value.parse() {
+                    Ok(workers) if workers > 0 => Some(workers),
+                    _ => return Err(InitError::Usage(format!("Invalid worker count {value}."))),
+                };
+            }
+            "--module-workers" => new_args.module_workers = true,
+            "--server" => new_args.server = true,
+            "--workspace" => new_args.workspace = true,
+            "-p" | "--path" => new_args.path = Some(value_for(&arg, args.next())?),
+            "-a" | "--answers" => new_args.answers = Some(value_for(&arg, args.next())?),
+            "--dry-run" => new_args.dry_run = true,
+            _ if arg.starts_with('-') => {
+                return Err(InitError::Usage(format!("Unknown option {arg}.")))
+            }
+            _ if new_args.name.is_none() => new_args.name = Some(check_name(arg)?),
+            _ => return Err(InitError::Usage(format!("Unexpected argument {arg}."))),
+        }
+    }
+
+    Ok(SubCommand::New(new_args))
+}
+
+fn parse_doctor<I: Iterator<Item = String>>(args: I) -> Result<SubCommand, InitError> {
+    let new_args = match parse_new(args)? {
+        SubCommand::New(new_args) => new_args,
+        other => return Ok(other),
+    };
+    // none of these change which tools are needed
+    if new_args.name.is_some()
+        || new_args.path.is_some()
+        || new_args.answers.is_some()
+        || new_args.dry_run
+        || new_args.registry.is_some()
+        || new_args.workers.is_some()
+        || new_args.module_workers
+    {
+        return Err(InitError::Usage(
+            "doctor only takes project type options.".to_string(),
+        ));
+    }
+    let framework = new_args.framework.unwrap_or(Framework::Vanilla);
+    check_rust_ui(framework, &new_args)?;
+
+    let any_set = new_args.framework.is_some()
+        || new_args.wasm.is_some()
+        || new_args.language.is_some()
+        || new_args.bundler.is_some()
+        || new_args.package_manager.is_some()
+        || new_args.offline
+        || new_args.server
+        || new_args.workspace;
+    let default_wasm = if framework.is_rust_ui() {
+        WasmMode::MainThread
+    } else {
+        WasmMode::Disabled
+    };
+    if !any_set {
+        return Ok(SubCommand::Doctor(DoctorArgs { spec: None }));
+    }
+    let spec = ProjectSpec {
+        framework,
+        wasm: new_args.wasm.unwrap_or(default_wasm),
+        language: new_args.language.unwrap_or(Language::JavaScript),
+        bundler: new_args.bundler.unwrap_or(None),
+        package_manager: match new_args.package_manager {
+            Some(package_manager) => package_manager,
+            None => doctor::detect_package_manager()?,
+        },
+        offline: new_args.offline,
+        registry: None,
+        workers: None,
+        module_workers: false,
+        server: new_args.server,
+        workspace: new_args.workspace,
+    };
+    spec.validate()?;
+    Ok(SubCommand::Doctor(DoctorArgs { spec: Some(spec) }))
+}
+
+fn parse_serve<I: Iterator<Item = String>>(mut args: I) -> Result<SubCommand, InitError> {
+    let mut dir = None;
+    let mut port = 8080;
+    let mut live_reload = true;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(SubCommand::Help),
+            "--port" => {
+                let value = value_for(&arg, args.next())?;
+                port = match value.parse() {
+                    Ok(ok) => ok,
+                    Err(_) => return Err(InitError::Usage(format!("Invalid port {value}."))),
+                };
+            }
+            "--no-reload" => live_reload = false,
+            _ if arg.starts_with('-') => {
+                return Err(InitError::Usage(format!("Unknown option {arg}.")))
+            }
+            _ if dir.is_none() => dir = Some(arg),
+            _ => return Err(InitError::Usage(format!("Unexpected argument {arg}."))),
+        }
+    }
+
+    Ok(SubCommand::Serve(ServeArgs {
+        dir: dir.unwrap_or_else(|| ".".to_string()),
+        port,
+        live_reload,
+    }))
+}
+
+fn parse_watch<I: Iterator<Item = String>>(args: I) -> Result<SubCommand, InitError> {
+    let mut dir = None;
+
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(SubCommand::Help),
+            _ if arg.starts_with('-') => {
+                return Err(InitError::Usage(format!("Unknown option {arg}.")))
+            }
+            _ if dir.is_none() => dir = Some(arg),
+            _ => return Err(InitError::Usage(format!("Unexpected argument {arg}."))),
+        }
+    }
+
+    Ok(SubCommand::Watch(dir.unwrap_or_else(|| ".".to_string())))
+}
+
+/// The Rust UI frameworks have no wasm mode, JavaScript or server to choose.
+pub fn check_rust_ui(framework: Framework, new_args: &NewArgs) -> Result<(), InitError> {
+    if framework.is_rust_ui()
+        && (new_args.wasm.is_some()
+            || new_args.language.is_some()
+            || new_args.bundler.is_some()
+            || new_args.server
+            || new_args.workspace)
+    {
+        return Err(InitError::Usage(format!(
+            "--wasm, --language, --bundler, --server and --workspace don't apply to {}.",
+            framework.slug()
+        )));
+    }
+    Ok(())
+}
+
+/// The project name becomes a directory name in the current directory (and
+/// its hidden staging directory), so it can't be a path.
+pub fn check_name(name: String) -> Result<String, InitError> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(InitError::Usage(format!(
+            "Invalid project name {name}; it can't contain / or \\ or .., use --path to pick where it goes."
+        )));
+    }
+    Ok(name)
+}
+
+fn value_for(flag: &str, value: Option<String>) -> Result<String, InitError> {
+    match value {
+        Some(value) if !value.starts_with('-') => Ok(value),
+        _ => Err(InitError::Usage(format!("Missing value for {flag}."))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<SubCommand, InitError> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn new_args(args: &[&str]) -> NewArgs {
+        match parse_args(args) {
+            Ok(SubCommand::New(new_args)) => new_args,
+            other => panic!("{args:?} parsed as {other:?}"),
+        }
+    }
+
+    fn usage_error(args: &[&str]) -> String {
+        match parse_args(args) {
+            Err(InitError::Usage(message)) => message,
+            other => panic!("{args:?} parsed as {other:?}"),
+        }
+    }
+
+    #[test]
+    fn new_is_optional() {
+        for args in [&["demo", "--offline"][..], &["new", "demo", "--offline"]] {
+            let new_args = new_args(args);
+            assert_eq!(new_args.name.as_deref(), Some("demo"));
+            assert!(new_args.offline);
+        }
+        assert_eq!(new_args(&[]).name, None);
+    }
+
+    #[test]
+    fn type_sets_framework_and_wasm() {
+        let new_args = new_args(&["--type", "react-wasm-worker"]);
+        assert_eq!(new_args.framework, Some(Framework::React));
+        assert_eq!(new_args.wasm, Some(WasmMode::Worker));
+        assert_eq!(
+            usage_error(&["-t", "svelte"]),
+            "Unknown project type svelte."
+        );
+    }
+
+    #[test]
+    fn values_cannot_look_like_options() {
+        assert_eq!(
+            usage_error(&["--path", "--dry-run"]),
+            "Missing value for --path."
+        );
+        assert_eq!(
+            usage_error(&["--framework"]),
+            "Missing value for --framework."
+        );
+    }
+
+    #[test]
+    fn rejects_a_second_name() {
+        assert_eq!(usage_error(&["new", "a", "b"]), "Unexpected argument b.");
+        assert_eq!(usage_error(&["a", "--bogus"]), "Unknown option --bogus.");
+    }
+
+    #[test]
+    fn rejects_unusable_names() {
+        for name in ["a/b", "a\\b", ".."] {
+            assert!(matches!(parse_args(&[name]), Err(InitError::Usage(_))));
+        }
+    }
+
+    #[test]
+    fn doctor_checks_its_options() {
+        assert_eq!(
+            usage_error(&["doctor", "--workers", "2"]),
+            "doctor only takes project type options."
+        );
+        assert_eq!(
+            usage_error(&["doctor", "--framework", "yew", "--server"]),
+            "--wasm, --language, --bundler, --server and --workspace don't apply to yew."
+        );
+        assert!(matches!(
+            parse_args(&["doctor"]),
+            Ok(SubCommand::Doctor(DoctorArgs { spec: None }))
+        ));
+    }
+}
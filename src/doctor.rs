@@ -0,0 +1,269 @@
+use std::process::Command;
+
+use crate::error::InitError;
+use crate::helpers::rust::THREADS_CHANNEL;
+use crate::spec::{PackageManager, ProjectSpec, WasmMode};
+
+/// Environment variable that sets the default package manager.
+pub const PACKAGE_MANAGER_VAR: &str = "INIT_WEB_APP_PACKAGE_MANAGER";
+
+/// An external tool a project type relies on and how to find out if it's there.
+pub struct Tool {
+    pub name: &'static str,
+    program: &'static str,
+    args: &'static [&'static str],
+    /// Line the output must contain; when unset the first line is the version.
+    expect: Option<&'static str>,
+    hint: &'static str,
+}
+
+pub const CARGO: Tool = Tool {
+    name: "cargo",
+    program: "cargo",
+    args: &["--version"],
+    expect: None,
+    hint: "install Rust from https://rustup.rs",
+};
+
+pub const WASM_TARGET: Tool = Tool {
+    name: "wasm32-unknown-unknown target",
+    program: "rustup",
+    args: &["target", "list", "--installed"],
+    expect: Some("wasm32-unknown-unknown"),
+    hint: "rustup target add wasm32-unknown-unknown",
+};
+
+/// The pinned nightly wasm threads build with, and the std sources its
+/// `build-std` recompiles. rustup adds the wasm target from the project's
+/// rust-toolchain.toml.
+pub const THREADS_TOOLCHAIN: Tool = Tool {
+    name: "nightly with rust-src for wasm threads",
+    program: "rustup",
+    args: &[
+        "component",
+        "list",
+        "--toolchain",
+        THREADS_CHANNEL,
+        "--installed",
+    ],
+    expect: Some("rust-src"),
+    hint: concat!(
+        "rustup toolchain install ",
+        crate::threads_channel!(),
+        " --component rust-src --target wasm32-unknown-unknown"
+    ),
+};
+
+pub const WASM_PACK: Tool = Tool {
+    name: "wasm-pack",
+    program: "wasm-pack",
+    args: &["--version"],
+    expect: None,
+    hint: "cargo install wasm-pack",
+};
+
+pub const WASM_BINDGEN: Tool = Tool {
+    name: "wasm-bindgen",
+    program: "wasm-bindgen",
+    args: &["--version"],
+    expect: None,
+    hint: "cargo install wasm-bindgen-cli, matching the project's wasm-bindgen version",
+};
+
+pub const TRUNK: Tool = Tool {
+    name: "trunk",
+    program: "trunk",
+    args: &["--version"],
+    expect: None,
+    hint: "cargo install --locked trunk",
+};
+
+pub const NPM: Tool = Tool {
+    name: "npm",
+    program: "npm",
+    args: &["--version"],
+    expect: None,
+    hint: "install Node.js from https://nodejs.org",
+};
+
+pub const PNPM: Tool = Tool {
+    name: "pnpm",
+    program: "pnpm",
+    args: &["--version"],
+    expect: None,
+    hint: "npm install -g pnpm, or corepack enable",
+};
+
+pub const YARN: Tool = Tool {
+    name: "yarn",
+    program: "yarn",
+    args: &["--version"],
+    expect: None,
+    hint: "npm install -g yarn, or corepack enable",
+};
+
+pub const BUN: Tool = Tool {
+    name: "bun",
+    program: "bun",
+    args: &["--version"],
+    expect: None,
+    hint: "install Bun from https://bun.sh",
+};
+
+/// Every tool, checked by `doctor` when no project type is given.
+pub const ALL: [&Tool; 10] = [
+    &CARGO,
+    &WASM_TARGET,
+    &THREADS_TOOLCHAIN,
+    &WASM_PACK,
+    &WASM_BINDGEN,
+    &TRUNK,
+    &NPM,
+    &PNPM,
+    &YARN,
+    &BUN,
+];
+
+impl Tool {
+    /// Returns the detected version, or `None` if the tool isn't usable.
+    pub fn detect(&self) -> Option<String> {
+        let output = Command::new(self.program).args(self.args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        match self.expect {
+            Some(expect) => stdout
+                .lines()
+                .any(|line| line.trim() == expect)
+                .then(|| "installed".to_string()),
+            None => stdout.lines().next().map(|line| line.trim().to_string()),
+        }
+    }
+
+    /// The `(major, minor)` of a tool whose first output line is its version,
+    /// such as `1.22.19`.
+    pub fn version(&self) -> Option<(u32, u32)> {
+        let version = self.detect()?;
+        let mut parts = version.trim_start_matches('v').split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        Some((major, minor))
+    }
+}
+
+/// Tools needed to generate and build `spec`.
+pub fn required(spec: &ProjectSpec) -> Vec<&'static Tool> {
+    let mut tools = Vec::new();
+    if spec.framework.is_rust_ui() {
+        tools.extend([&CARGO, &WASM_TARGET, &TRUNK]);
+        if spec.offline {
+            // neither can Trunk
+            tools.push(&WASM_BINDGEN);
+        }
+        return tools;
+    }
+    match spec.wasm {
+        WasmMode::Disabled => (),
+        // the stable wasm target goes unused
+        WasmMode::Threads => tools.extend([&CARGO, &THREADS_TOOLCHAIN, &WASM_PACK]),
+        _ => tools.extend([&CARGO, &WASM_TARGET, &WASM_PACK]),
+    }
+    if spec.wasm != WasmMode::Disabled && spec.offline {
+        // wasm-pack can't download it
+        tools.push(&WASM_BINDGEN);
+    }
+    if spec.server && spec.wasm == WasmMode::Disabled {
+        tools.push(&CARGO);
+    }
+    if spec.uses_npm() {
+        tools.push(package_manager(spec.package_manager));
+    }
+    tools
+}
+
+pub fn package_manager(package_manager: PackageManager) -> &'static Tool {
+    match package_manager {
+        PackageManager::Npm => &NPM,
+        PackageManager::Pnpm => &PNPM,
+        PackageManager::Yarn => &YARN,
+        PackageManager::Bun => &BUN,
+    }
+}
+
+/// The package manager named by `INIT_WEB_APP_PACKAGE_MANAGER`, else the one
+/// this tool was launched through (as with `pnpm dlx`), otherwise the first
+/// of pnpm, yarn and bun that is installed. npm comes with Node.js, so having
+/// another one installed is taken as a preference.
+pub fn detect_package_manager() -> Result<PackageManager, InitError> {
+    if let Ok(configured) = std::env::var(PACKAGE_MANAGER_VAR) {
+        return PackageManager::from_slug(&configured).map_err(|_| {
+            InitError::Usage(format!(
+                "{PACKAGE_MANAGER_VAR} must be npm, pnpm, yarn or bun, not {configured:?}."
+            ))
+        });
+    }
+    let user_agent = std::env::var("npm_config_user_agent").unwrap_or_default();
+    let launched_by = PackageManager::ALL
+        .into_iter()
+        .find(|pm| user_agent.starts_with(&format!("{}/", pm.slug())));
+    if let Some(pm) = launched_by {
+        return Ok(pm);
+    }
+    let installed = [
+        PackageManager::Pnpm,
+        PackageManager::Yarn,
+        PackageManager::Bun,
+    ]
+    .into_iter()
+    .find(|pm| package_manager(*pm).detect().is_some());
+    Ok(installed.unwrap_or(PackageManager::Npm))
+}
+
+/// Prints a line per tool and returns the names of the missing ones.
+pub fn report(tools: &[&'static Tool]) -> Vec<&'static str> {
+    let mut missing = Vec::new();
+    for tool in tools {
+        match tool.detect() {
+            Some(version) => println!("    ok       {}: {version}", tool.name),
+            None => {
+                println!("    missing  {}: {}", tool.name, tool.hint);
+                missing.push(tool.name);
+            }
+        }
+    }
+    missing
+}
+
+/// Checks `tools` and fails with [`InitError::MissingTools`] if any are absent.
+pub fn check(tools: &[&'static Tool]) -> Result<(), InitError> {
+    if tools.is_empty() {
+        return Ok(());
+    }
+    println!("Checking tools:");
+    let missing = report(tools);
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(InitError::MissingTools(missing))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_lists_every_tool() {
+        let declared = include_str!("doctor.rs")
+            .lines()
+            .filter(|line| line.starts_with("pub const ") && line.ends_with(": Tool = Tool {"))
+            .count();
+        assert_eq!(ALL.len(), declared);
+
+        let mut names: Vec<&str> = ALL.iter().map(|tool| tool.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), ALL.len(), "a tool is listed twice");
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+use std::io;
+use std::process::ExitStatus;
+
+/// Everything that can stop a run. Each variant maps to its own exit code so
+/// wrapper scripts can tell failures apart without parsing messages.
+#[derive(Debug)]
+pub enum InitError {
+    /// Bad command-line arguments.
+    Usage(String),
+    /// No usable answer could be read for a prompt.
+    Input(String),
+    /// The project directory is already there.
+    Exists { path: String },
+    /// A file or directory operation failed.
+    Io {
+        step: &'static str,
+        path: String,
+        source: io::Error,
+    },
+    /// An external tool could not be started.
+    Spawn { command: String, source: io::Error },
+    /// An external tool ran but exited unsuccessfully.
+    Command {
+        command: String,
+        status: ExitStatus,
+        stderr: String,
+        log: String,
+    },
+    /// Tools the project type needs are not installed.
+    MissingTools(Vec<&'static str>),
+    /// The run was stopped with Ctrl-C.
+    Interrupted,
+}
+
+impl InitError {
+    /// Builds a `map_err` adapter for an io failure while doing `step` on `path`.
+    pub fn io(step: &'static str, path: &str) -> impl FnOnce(io::Error) -> InitError {
+        let path = path.to_string();
+        move |source| InitError::Io { step, path, source }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            InitError::Usage(_) => 2,
+            InitError::Input(_) => 3,
+            InitError::Exists { .. } => 4,
+            InitError::Io { .. } => 5,
+            InitError::Spawn { .. } => 6,
+            InitError::Command { .. } => 7,
+            InitError::MissingTools(_) => 8,
+            InitError::Interrupted => 130,
+        }
+    }
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::Usage(message) | InitError::Input(message) => write!(f, "{message}"),
+            InitError::Exists { path } => write!(f, "Path already exists {path}."),
+            InitError::Io { step, path, source } => write!(f, "Failed to {step} {path}: {source}"),
+            InitError::Spawn { command, source } => {
+                write!(f, "Failed to run `{command}`: {source}")
+            }
+            InitError::Command {
+                command,
+                status,
+                stderr,
+                log,
+            } => write!(f, "`{command}` failed ({status}), see {log}:\n{stderr}"),
+            InitError::MissingTools(tools) => write!(f, "Missing {}.", tools.join(", ")),
+            InitError::Interrupted => write!(f, "Interrupted."),
+        }
+    }
+}
+
+impl std::error::Error for InitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InitError::Io { source, .. } | InitError::Spawn { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
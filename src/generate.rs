@@ -0,0 +1,709 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use crate::doctor;
+use crate::error::InitError;
+use crate::helpers;
+use crate::helpers::html::React;
+use crate::interrupt;
+use crate::package::Package;
+use crate::spec::{self, Bundler, Framework, Language, ProjectSpec, WasmMode};
+
+impl ProjectSpec {
+    /// Generates the project in a staging directory next to its final location
+    /// and renames it into place once every step has succeeded. On failure the
+    /// staging directory is removed, keeping only its `init.log`.
+    pub fn init(&self, project_name: &str) -> Result<(), InitError> {
+        println!("starting initiation of project: {}", project_name);
+        if Path::new(project_name).exists() {
+            return Err(InitError::Exists {
+                path: project_name.to_string(),
+            });
+        }
+
+        let project = Project {
+            name: project_name.to_string(),
+            dir: format!(".{project_name}.partial"),
+            dry_run: None,
+        };
+        // left behind by a run that was killed outright
+        if Path::new(&project.dir).exists() {
+            std::fs::remove_dir_all(&project.dir)
+                .map_err(InitError::io("remove stale", &project.dir))?;
+        }
+
+        let generated = self.generate(&project).and_then(|()| {
+            std::fs::rename(&project.dir, project_name)
+                .map_err(InitError::io("move into place", project_name))
+        });
+        match generated {
+            Ok(()) => {
+                println!("Done!");
+                Ok(())
+            }
+            Err(mut e) => {
+                println!("Removing partially generated project.");
+                let kept_log = format!("{project_name}.init.log");
+                if std::fs::rename(project.path("init.log"), &kept_log).is_ok() {
+                    if let InitError::Command { log, .. } = &mut e {
+                        *log = kept_log;
+                    }
+                }
+                let _ = std::fs::remove_dir_all(&project.dir);
+                Err(e)
+            }
+        }
+    }
+
+    /// Prints every directory, file and command [`ProjectSpec::init`] would
+    /// create or run, followed by the resulting file tree, without touching disk.
+    pub fn dry_run(&self, project_name: &str) -> Result<(), InitError> {
+        println!("dry run of project: {}", project_name);
+        let project = Project {
+            name: project_name.to_string(),
+            dir: project_name.to_string(),
+            dry_run: Some(RefCell::new(Vec::new())),
+        };
+        self.generate(&project)?;
+
+        if let Some(planned) = &project.dry_run {
+            let mut planned = planned.borrow_mut();
+            planned.sort();
+            planned.dedup();
+            println!("\nFiles:");
+            print_tree(&project.dir, &planned);
+        }
+        Ok(())
+    }
+
+    fn generate(&self, project: &Project) -> Result<(), InitError> {
+        let project_name = project.name.as_str();
+        mkdir(project, "")?; // project directory
+
+        // package directory
+        mkdir(project, "pkg")?;
+        write_file(project, spec::MANIFEST, self.to_manifest(project_name))?;
+        if self.framework.is_rust_ui() {
+            return self.generate_rust_ui(project);
+        }
+
+        // build steps as (package.json script name, command), used for scripts and readme
+        let mut build_steps: Vec<(&str, String)> = Vec::new();
+
+        let typescript = self.language == Language::TypeScript;
+        let react = self.framework == Framework::React;
+        let source = self.source_file(project_name);
+
+        println!("Generating HTML.");
+        let page_script = self.page_script(project_name);
+        let html = match (self.framework, self.bundler) {
+            (Framework::React, Some(_)) => {
+                helpers::html::html_react(project_name, &page_script, React::Bundled)
+            }
+            (Framework::React, None) if self.offline => {
+                helpers::html::html_react(project_name, &page_script, React::Vendored)
+            }
+            (Framework::React, None) => {
+                helpers::html::html_react(project_name, &page_script, React::Unpkg)
+            }
+            _ => helpers::html::html(project_name),
+        };
+        gen_html(project, html)?;
+        println!("Generating CSS.");
+        gen_css(project, helpers::css::css())?;
+
+        let module_glue = format!("{}/{project_name}_wasm.js", self.wasm_import_dir());
+        let module_glue = self.uses_module_workers().then_some(module_glue.as_str());
+        let wasm_loader = match self.wasm {
+            WasmMode::Disabled => String::new(),
+            WasmMode::MainThread => {
+                helpers::js::vanilla_js_wasm(project_name, self.wasm_import_dir())
+            }
+            WasmMode::Worker | WasmMode::Threads => {
+                helpers::js::vanilla_js_wasm_worker_main(project_name, typescript, module_glue)
+            }
+            WasmMode::WorkerPool => helpers::js::vanilla_js_wasm_worker_pool(
+                project_name,
+                self.workers,
+                typescript,
+                module_glue,
+            ),
+        };
+        let script = if react {
+            // babel leaves import paths alone, bundlers resolve the extension
+            let app_import = match self.bundler {
+                None => "./App.js",
+                Some(_) => "./App",
+            };
+            helpers::jsx::main(app_import, &wasm_loader, typescript)
+        } else if self.wasm == WasmMode::Disabled {
+            helpers::js::vanilla_js(project_name)
+        } else {
+            wasm_loader
+        };
+        let script = if self.server && !react {
+            script + &helpers::js::api_fetch()
+        } else {
+            script
+        };
+        if react {
+            mkdir(project, spec::APP_DIR)?;
+        }
+        println!("Generating {source}.");
+        write_file(project, &source, script)?;
+        if let Some(app_file) = self.app_file() {
+            println!("Generating {app_file}.");
+            write_file(
+                project,
+                &app_file,
+                helpers::jsx::app(project_name, self.server, typescript),
+            )?;
+        }
+        if react && self.bundler.is_none() {
+            mkdir(project, "pkg/react")?;
+            for (path, module) in helpers::jsx::UMD_MODULES {
+                write_file(project, path, module.to_string())?;
+            }
+        }
+
+        let mut dev_script = None;
+        let build = match self.bundler {
+            Some(Bundler::Vite) => {
+                dev_script = Some(helpers::bundler::vite_dev());
+                Some(helpers::bundler::vite_build())
+            }
+            Some(Bundler::Esbuild) => {
+                dev_script = Some(helpers::bundler::esbuild_dev(project_name, &source));
+                Some(helpers::bundler::esbuild_build(project_name, &source))
+            }
+            None if typescript => Some(helpers::typescript::build_command(&source)),
+            None if react => Some(helpers::babel::build_command(false)),
+            None => None,
+        };
+        if let Some(build) = build {
+            let build = match self.bundler {
+                Some(_) if typescript => helpers::typescript::type_checked(&build),
+                _ => build,
+            };
+            build_steps.push(("build", build));
+        }
+
+        // JavaScript even in TypeScript projects: it runs in a worker scope
+        // the page's DOM typed tsconfig can't check, and is only the RPC loop
+        if self.wasm.in_worker() {
+            println!("Generating JS Worker");
+            gen_js_worker(
+                project,
+                helpers::js::vanilla_js_wasm_worker_sub(
+                    project_name,
+                    self.uses_module_workers(),
+                    self.wasm == WasmMode::Threads,
+                ),
+            )?;
+        }
+        if let Some(target) = self.wasm_target() {
+            build_steps.push((
+                "wasm",
+                helpers::commands::wasm_build(target, typescript, self.offline, self.workspace),
+            ));
+        }
+
+        if self.uses_npm() {
+            let mut package = Package::new(project_name);
+            match self.bundler {
+                Some(Bundler::Vite) => {
+                    package
+                        .dev_dependencies
+                        .extend(helpers::bundler::vite_packages(react));
+                }
+                Some(Bundler::Esbuild) => {
+                    package
+                        .dev_dependencies
+                        .extend(helpers::bundler::esbuild_packages());
+                }
+                None if react => {
+                    package
+                        .dev_dependencies
+                        .extend(helpers::babel::packages(typescript));
+                }
+                None => (),
+            }
+            if typescript {
+                package
+                    .dev_dependencies
+                    .extend(helpers::typescript::packages(react));
+            }
+            if react && (self.bundler.is_some() || self.offline) {
+                package.dependencies.extend(helpers::jsx::react_packages());
+            }
+            package.scripts = build_steps
+                .iter()
+                .map(|(name, command)| (name.to_string(), command.clone()))
+                .collect();
+            if let Some(dev) = &dev_script {
+                package.scripts.push(("dev".to_string(), dev.clone()));
+            }
+            gen_package_json(project, &package)?;
+
+            match self.bundler {
+                Some(Bundler::Vite) => write_file(
+                    project,
+                    "vite.config.js",
+                    helpers::bundler::vite_config(project_name, &source, react),
+                )?,
+                Some(Bundler::Esbuild) => (),
+                None if react => gen_babel_config(project, helpers::babel::config(typescript))?,
+                None => (),
+            }
+            if typescript {
+                // the worker's no-modules glue declares a global, not a module
+                let worker_glue = (self.wasm_target() == Some("no-modules"))
+                    .then(|| format!("pkg/{project_name}_wasm.d.ts"));
+                write_file(
+                    project,
+                    "tsconfig.json",
+                    helpers::typescript::tsconfig(
+                        &source,
+                        self.bundler.is_none(),
+                        worker_glue.as_deref(),
+                    ),
+                )?;
+            }
+
+            if let Some(registry) = &self.registry {
+                let version = doctor::package_manager(self.package_manager).version();
+                let (file, config) = self.package_manager.registry_config(version, registry);
+                write_file(project, file, config)?;
+            }
+            let package_manager = self.package_manager.slug();
+            println!("Installing packages with {package_manager}.");
+            let install_args = if self.offline {
+                self.package_manager.offline_install_args()
+            } else {
+                vec!["install"]
+            };
+            run_in_project(project, package_manager, &install_args)?;
+
+            if react && self.bundler.is_none() && self.offline {
+                println!("Copying React into pkg/vendor.");
+                mkdir(project, "pkg/vendor")?;
+                for (from, to) in helpers::jsx::REACT_UMD {
+                    copy_file(project, from, to)?;
+                }
+            }
+        }
+
+        let shared_crate = self
+            .workspace
+            .then(|| helpers::shared::crate_name(project_name));
+        let shared_crate = shared_crate.as_deref();
+        let server_crate = helpers::server::crate_name(project_name);
+        let mut members = Vec::new();
+        if self.server {
+            println!("Generating server crate.");
+            gen_server_crate(project, self.server_crate_dir(), shared_crate)?;
+            members.push(self.server_crate_dir());
+            if self.wasm_target().is_none() {
+                write_file(project, "Cargo.toml", helpers::rust::workspace(&members))?;
+                // there is no `cargo init` to write one
+                gen_gitignore(project)?;
+            }
+        }
+
+        // built first so typescript can check against its declarations
+        if let Some(target) = self.wasm_target() {
+            println!("Generating Rust lib.");
+            let threads = self.wasm == WasmMode::Threads;
+            let lib = match self.wasm {
+                WasmMode::Threads => helpers::rust::wasm_threads(shared_crate),
+                _ => helpers::rust::wasm(self.wasm.in_worker(), shared_crate),
+            };
+            gen_rust_project(
+                project,
+                self.wasm_crate_dir(),
+                &format!("{project_name}_wasm"),
+                helpers::rust::toml(threads, shared_crate),
+                lib,
+            )?;
+            if let Some(shared_crate) = shared_crate {
+                println!("Generating shared crate.");
+                gen_rust_project(
+                    project,
+                    spec::SHARED_CRATE_DIR,
+                    shared_crate,
+                    helpers::shared::toml(),
+                    helpers::shared::lib(),
+                )?;
+                members.splice(0..0, [spec::WASM_CRATE_DIR, spec::SHARED_CRATE_DIR]);
+                write_file(project, "Cargo.toml", helpers::rust::workspace(&members))?;
+                // the crates are made without one, as parts of the project
+                gen_gitignore(project)?;
+            } else if self.server {
+                append_file(
+                    project,
+                    "Cargo.toml",
+                    helpers::rust::workspace_section(&members),
+                )?;
+            }
+            if threads {
+                write_file(
+                    project,
+                    "rust-toolchain.toml",
+                    helpers::rust::threads_toolchain(),
+                )?;
+                mkdir(project, ".cargo")?;
+                write_file(
+                    project,
+                    ".cargo/config.toml",
+                    helpers::rust::threads_cargo_config(),
+                )?;
+            }
+            println!("Compiling Rust Lib.");
+            run_in_project(
+                project,
+                "wasm-pack",
+                &helpers::commands::wasm_pack_args(
+                    target,
+                    typescript,
+                    self.offline,
+                    self.workspace,
+                ),
+            )?;
+        }
+
+        if self.uses_npm() {
+            println!("Building {source}.");
+            run_in_project(
+                project,
+                self.package_manager.slug(),
+                &helpers::commands::build_args(),
+            )?;
+        }
+
+        if self.server {
+            println!("Building server crate.");
+            run_in_project(
+                project,
+                "cargo",
+                &helpers::commands::server_build_args(&server_crate, self.offline),
+            )?;
+        }
+
+        if !build_steps.is_empty() || self.server {
+            println!("Generating readme.");
+            let mut readme = if build_steps.is_empty() {
+                String::new()
+            } else if self.uses_npm() {
+                let package_manager = self.package_manager.slug();
+                let dev = dev_script.map(|dev| helpers::readme::dev(package_manager, &dev));
+                helpers::readme::scripts(
+                    package_manager,
+                    self.package_manager
+                        .lockfile(doctor::package_manager(self.package_manager).version()),
+                    &build_steps,
+                ) + &dev.unwrap_or_default()
+            } else {
+                helpers::readme::plain(&build_steps)
+            };
+            if self.server {
+                readme += &helpers::readme::server(&server_crate);
+            }
+            gen_readme(project, readme.trim_start().to_string())?;
+        }
+        Ok(())
+    }
+}
+
+impl ProjectSpec {
+    /// A Rust UI framework app: one binary crate that Trunk builds from
+    /// `index.html` into `pkg/`.
+    fn generate_rust_ui(&self, project: &Project) -> Result<(), InitError> {
+        let project_name = project.name.as_str();
+        let (dependency, main, mount) = match self.framework {
+            Framework::Yew => (
+                helpers::rust_ui::yew_toml(),
+                helpers::rust_ui::yew(project_name),
+                "",
+            ),
+            Framework::Leptos => (
+                helpers::rust_ui::leptos_toml(),
+                helpers::rust_ui::leptos(project_name),
+                "",
+            ),
+            // dioxus-web mounts on #main
+            Framework::Dioxus => (
+                helpers::rust_ui::dioxus_toml(),
+                helpers::rust_ui::dioxus(project_name),
+                "\n    <div id=\"main\"></div>\n",
+            ),
+            Framework::Vanilla | Framework::React => unreachable!("not a Rust UI framework"),
+        };
+
+        println!("Generating Rust app.");
+        run_in_project(
+            project,
+            "cargo",
+            &["init", ".", "--name", project_name, "--bin"],
+        )?;
+        append_file(project, "Cargo.toml", dependency)?;
+        write_file(project, "src/main.rs", main)?;
+
+        println!("Generating HTML.");
+        write_file(
+            project,
+            "index.html",
+            helpers::rust_ui::index_html(project_name, mount),
+        )?;
+        println!("Generating CSS.");
+        write_file(
+            project,
+            &format!("{project_name}_styles.css"),
+            helpers::css::css(),
+        )?;
+        write_file(project, "Trunk.toml", helpers::rust_ui::trunk_toml())?;
+
+        println!("Building with Trunk.");
+        run_in_project(
+            project,
+            "trunk",
+            &helpers::commands::trunk_args(self.offline),
+        )?;
+
+        println!("Generating readme.");
+        let build = helpers::commands::trunk_build(self.offline);
+        gen_readme(project, helpers::readme::plain(&[("build", build)]))
+    }
+}
+
+/// Where a project is being generated. `dir` differs from `name` while the
+/// project is still in its staging directory.
+struct Project {
+    name: String,
+    dir: String,
+    /// Set for dry runs, collecting the paths that would be written.
+    dry_run: Option<RefCell<Vec<String>>>,
+}
+
+impl Project {
+    fn path(&self, relative: &str) -> String {
+        format!("{}/{relative}", self.dir)
+    }
+
+    /// In a dry run, prints `action` and `contents` and records `relative` as
+    /// written. Returns whether this is a dry run.
+    fn plan(&self, action: &str, relative: &str, contents: &str) -> bool {
+        let Some(planned) = &self.dry_run else {
+            return false;
+        };
+        println!("--- {action}");
+        if !contents.is_empty() {
+            println!("{contents}");
+        }
+        if !relative.is_empty() {
+            planned.borrow_mut().push(relative.to_string());
+        }
+        true
+    }
+}
+
+fn print_tree(root: &str, paths: &[String]) {
+    println!("{root}/");
+    let mut printed: Vec<&str> = Vec::new();
+    for path in paths {
+        let parts: Vec<&str> = path.split('/').collect();
+        for depth in 0..parts.len() {
+            let prefix = &path[..parts[..=depth].join("/").len()];
+            if printed.contains(&prefix) {
+                continue;
+            }
+            printed.push(prefix);
+            let slash = if depth + 1 < parts.len() { "/" } else { "" };
+            println!("{}{}{slash}", "    ".repeat(depth + 1), parts[depth]);
+        }
+    }
+}
+
+fn gen_babel_config(project: &Project, config: String) -> Result<(), InitError> {
+    write_file(project, "babel.config.json", config)
+}
+
+/// Keeps the workspace's `target/` out of git.
+fn gen_gitignore(project: &Project) -> Result<(), InitError> {
+    write_file(project, ".gitignore", "/target\n".to_string())
+}
+
+fn gen_readme(project: &Project, readme: String) -> Result<(), InitError> {
+    write_file(project, "readme.txt", readme)
+}
+
+/// Creates a lib crate in `dir`, `.` being the project itself.
+fn gen_rust_project(
+    project: &Project,
+    dir: &str,
+    crate_name: &str,
+    toml: String,
+    lib: String,
+) -> Result<(), InitError> {
+    // create rust lib
+    let mut args = vec!["init", dir, "--name", crate_name, "--lib"];
+    if dir != "." {
+        // the project root is the repository
+        args.extend(["--vcs", "none"]);
+    }
+    run_in_project(project, "cargo", &args)?;
+
+    // modify toml file
+    append_file(project, &in_dir(dir, "Cargo.toml"), toml)?;
+
+    // make new lib
+    write_file(project, &in_dir(dir, "src/lib.rs"), lib)
+}
+
+/// Writes the axum crate into `dir`, using the API types of `shared` if given.
+fn gen_server_crate(project: &Project, dir: &str, shared: Option<&str>) -> Result<(), InitError> {
+    mkdir(project, &format!("{dir}/src"))?;
+    write_file(
+        project,
+        &format!("{dir}/Cargo.toml"),
+        helpers::server::toml(&project.name, shared),
+    )?;
+    write_file(
+        project,
+        &format!("{dir}/src/main.rs"),
+        helpers::server::main(&project.name, shared),
+    )
+}
+
+fn in_dir(dir: &str, relative: &str) -> String {
+    match dir {
+        "." => relative.to_string(),
+        _ => format!("{dir}/{relative}"),
+    }
+}
+
+/// Runs `program` inside the project directory, appending its output to
+/// `init.log` there. A non-zero exit is an error carrying the command's stderr.
+fn run_in_project(project: &Project, program: &str, args: &[&str]) -> Result<(), InitError> {
+    let command = format!("{program} {}", args.join(" "));
+    if project.plan(&format!("run in {}", project.dir), "", &command) {
+        return Ok(());
+    }
+    let output = match Command::new(program)
+        .current_dir(&project.dir)
+        .args(args)
+        .output()
+    {
+        Ok(ok) => ok,
+        Err(source) => return Err(InitError::Spawn { command, source }),
+    };
+
+    let log = project.path("init.log");
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log)
+        .and_then(|mut log| {
+            writeln!(log, "$ {command}\n{}", output.status)?;
+            log.write_all(&output.stdout)?;
+            log.write_all(&output.stderr)?;
+            writeln!(log)
+        })
+        .map_err(InitError::io("write", &log))?;
+
+    if interrupt::interrupted() {
+        return Err(InitError::Interrupted);
+    }
+    if !output.status.success() {
+        return Err(InitError::Command {
+            command,
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr)
+                .trim_end()
+                .to_string(),
+            log,
+        });
+    }
+    Ok(())
+}
+
+/// Writes `package`, merging it into a package.json that is already there.
+fn gen_package_json(project: &Project, package: &Package) -> Result<(), InitError> {
+    let path = project.path("package.json");
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(existing) => Some(existing),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(InitError::io("read", &path)(e)),
+    };
+    let json = match existing {
+        Some(existing) => package
+            .merge(&existing)
+            .map_err(|e| InitError::Input(format!("{path} is not a valid package.json: {e}")))?,
+        None => package.to_json(),
+    };
+    write_file(project, "package.json", json)
+}
+
+fn gen_js_worker(project: &Project, js: String) -> Result<(), InitError> {
+    write_file(project, &format!("pkg/{}_worker.js", project.name), js)
+}
+
+fn gen_css(project: &Project, css: String) -> Result<(), InitError> {
+    write_file(project, &format!("pkg/{}_styles.css", project.name), css)
+}
+
+fn gen_html(project: &Project, html: String) -> Result<(), InitError> {
+    write_file(project, &format!("pkg/{}.html", project.name), html)
+}
+
+fn write_file(project: &Project, relative: &str, contents: String) -> Result<(), InitError> {
+    let path = project.path(relative);
+    if project.plan(&format!("write {path}"), relative, &contents) {
+        return Ok(());
+    }
+    if interrupt::interrupted() {
+        return Err(InitError::Interrupted);
+    }
+    std::fs::write(&path, contents).map_err(InitError::io("write", &path))
+}
+
+fn append_file(project: &Project, relative: &str, contents: String) -> Result<(), InitError> {
+    let path = project.path(relative);
+    if project.plan(&format!("append to {path}"), relative, &contents) {
+        return Ok(());
+    }
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(InitError::io("append to", &path))
+}
+
+fn copy_file(project: &Project, from: &str, to: &str) -> Result<(), InitError> {
+    let (from, to_path) = (project.path(from), project.path(to));
+    if project.plan(&format!("copy {from} to {to_path}"), to, "") {
+        return Ok(());
+    }
+    if interrupt::interrupted() {
+        return Err(InitError::Interrupted);
+    }
+    std::fs::copy(&from, &to_path)
+        .map(|_| ())
+        .map_err(InitError::io("copy", &from))
+}
+
+fn mkdir(project: &Project, relative: &str) -> Result<(), InitError> {
+    let path = match relative {
+        "" => project.dir.clone(),
+        _ => project.path(relative),
+    };
+    if project.plan(&format!("mkdir {path}"), "", "") {
+        return Ok(());
+    }
+    if Path::new(&path).exists() {
+        return Err(InitError::Exists { path });
+    }
+
+    std::fs::create_dir_all(&path).map_err(InitError::io("create directory", &path))
+}
@@ -3,167 +3,586 @@ pub mod js {
         format!("alert(\"Hello {project_name} from JS!\");")
     }
 
-    pub fn vanilla_js_wasm(project_name: &str) -> String {
+    /// Shows the message from the server crate's `/api/hello` on the page.
+    pub fn api_fetch() -> String {
+        "
+fetch(\"/api/hello\")
+    .then((response) => response.json())
+    .then((greeting) => {
+        const message = document.createElement(\"p\");
+        message.textContent = greeting.message;
+        document.body.append(message);
+    })
+    .catch((e) => console.error(\"/api/hello failed\", e));
+"
+        .to_string()
+    }
+
+    pub fn vanilla_js_wasm(project_name: &str, wasm_dir: &str) -> String {
         format!(
             "
-import init, {{hello_wasm}} from \"./{project_name}_wasm.js\";
+import init, {{hello_wasm}} from \"{wasm_dir}/{project_name}_wasm.js\";
 await init();
 
 alert(hello_wasm(\"{project_name}\"));\n"
         )
     }
 
-    pub fn vanilla_js_wasm_worker_main(project_name: &str) -> String {
+    /// Starts the worker and exposes its wasm exports as `wasm`, whose
+    /// methods post `{ id, method, args }` and resolve to the worker's
+    /// response. Calls wait until the worker reports its wasm loaded; a
+    /// failed start, a throwing export or a crashed worker rejects them.
+    ///
+    /// `module_glue` is the import path of the `--target web` glue for module
+    /// workers; classic workers use the `no-modules` one.
+    pub fn vanilla_js_wasm_worker_main(
+        project_name: &str,
+        typescript: bool,
+        module_glue: Option<&str>,
+    ) -> String {
+        // annotations only written into TypeScript
+        let ts = |annotation: &'static str| if typescript { annotation } else { "" };
+        let types = ts(REMOTE_TYPES);
+        let void = ts("<void>");
+        let map_types = ts("<number, Pending>");
+        let method_type = ts(": string");
+        let args_type = ts(": unknown[]");
+        let proxy = wasm_proxy(typescript, module_glue);
+        let options = worker_options(module_glue);
         format!(
             "
-let worker = new Worker(\"{project_name}_worker.js\");
+const worker = new Worker(\"{project_name}_worker.js\"{options});
+{types}
+// settles once the worker has loaded its wasm, or failed to
+const ready = new Promise{void}((resolve, reject) => {{
+    worker.addEventListener(\"message\", (e) => {{
+        if (e.data.type === \"ready\") resolve();
+        if (e.data.type === \"error\") reject(new Error(e.data.message));
+    }});
+    worker.addEventListener(\"error\", (e) => reject(new Error(e.message)));
+}});
 
-await new Promise(r => setTimeout(r, 250)); // let wasm load
+// calls waiting for their response, by request id
+const pending = new Map{map_types}();
+let nextId = 0;
 
-worker.onmessage = (e) => {{
-    alert(e.data)
-}}
+worker.addEventListener(\"message\", (e) => {{
+    const call = e.data.type === \"response\" && pending.get(e.data.id);
+    if (!call) return;
+    pending.delete(e.data.id);
+    if (\"error\" in e.data) call.reject(new Error(e.data.error));
+    else call.resolve(e.data.result);
+}});
+worker.addEventListener(\"error\", (e) => {{
+    pending.forEach((call) => call.reject(new Error(e.message)));
+    pending.clear();
+}});
 
-worker.postMessage(true);
+const call = (method{method_type}, args{args_type}) =>
+    ready.then(
+        () =>
+            new Promise((resolve, reject) => {{
+                const id = nextId++;
+                pending.set(id, {{ resolve, reject }});
+                worker.postMessage({{ id, method, args }});
+            }}),
+    );
+
+{proxy}
+wasm.hello_wasm(\"{project_name} js worker\")
+    .then(alert)
+    .catch((error) => console.error(`{project_name} worker: ${{error.message}}`));
 "
         )
     }
 
-    pub fn vanilla_js_wasm_worker_sub(project_name: &str) -> String {
+    /// Starts `size` workers, or one per core, behind the same `wasm` proxy
+    /// as [`vanilla_js_wasm_worker_main`]. Calls wait in a queue and go to
+    /// whichever worker is idle next, one at a time each. A worker that fails
+    /// is dropped from the pool along with its call.
+    pub fn vanilla_js_wasm_worker_pool(
+        project_name: &str,
+        size: Option<u32>,
+        typescript: bool,
+        module_glue: Option<&str>,
+    ) -> String {
+        let ts = |annotation: &'static str| if typescript { annotation } else { "" };
+        let types = ts(r#"
+type Task = {
+    id: number;
+    method: string;
+    args: unknown[];
+    resolve: (value: unknown) => void;
+    reject: (error: Error) => void;
+};
+type Slot = { worker: Worker; task: Task | null; retired: boolean };
+"#);
+        let remote_types = ts(REMOTE_TYPES);
+        let queue_type = ts(": Task[]");
+        let idle_type = ts(": Slot[]");
+        let slot_type = ts(": Slot");
+        let error_type = ts(": Error");
+        let non_null = ts("!");
+        let method_type = ts(": string");
+        let args_type = ts(": unknown[]");
+        let size = match size {
+            Some(size) => size.to_string(),
+            None => "navigator.hardwareConcurrency || 4".to_string(),
+        };
+        let proxy = wasm_proxy(typescript, module_glue);
+        let options = worker_options(module_glue);
         format!(
             "
-importScripts(\"{project_name}_wasm.js\");
+const poolSize = {size};
+{types}{remote_types}
+// calls waiting for a worker, and workers waiting for a call
+const queue{queue_type} = [];
+const idle{idle_type} = [];
+let alive = poolSize;
+let nextId = 0;
 
-// not ordinarily necessary, but for streaming WASM compilation to
-// work it needs to be served with a content-type of application/wasm,
-// which isn't always the case (eg with php -S), so we remove for now:
-delete WebAssembly.instantiateStreaming;
+const dispatch = () => {{
+    while (queue.length > 0 && idle.length > 0) {{
+        const slot = idle.shift(){non_null};
+        const task = queue.shift(){non_null};
+        slot.task = task;
+        slot.worker.postMessage({{ id: task.id, method: task.method, args: task.args }});
+    }}
+}};
 
-// init the wasm file
-wasm_bindgen(\"{project_name}_wasm_bg.wasm\").then((wasm) => {{
-  // bing wasm functions into scope
-  const {{ hello_wasm }} = wasm_bindgen;
+// a worker that failed to start or crashed takes its call down with it, and
+// the last one everything still queued
+const retire = (slot{slot_type}, error{error_type}) => {{
+    if (slot.retired) return;
+    slot.retired = true;
+    slot.worker.terminate();
+    slot.task?.reject(error);
+    slot.task = null;
+    if (idle.includes(slot)) idle.splice(idle.indexOf(slot), 1);
+    if (--alive === 0) queue.splice(0).forEach((task) => task.reject(error));
+}};
 
-  onmessage = (e) => {{
-    postMessage(hello_wasm(\"{project_name} js worker\"));
+for (let i = 0; i < poolSize; i++) {{
+    const slot{slot_type} = {{ worker: new Worker(\"{project_name}_worker.js\"{options}), task: null, retired: false }};
+    slot.worker.addEventListener(\"message\", (e) => {{
+        if (e.data.type === \"error\") return retire(slot, new Error(e.data.message));
+        const task = slot.task;
+        if (e.data.type === \"response\" && task) {{
+            slot.task = null;
+            if (\"error\" in e.data) task.reject(new Error(e.data.error));
+            else task.resolve(e.data.result);
+        }}
+        // ready, or done with its call
+        idle.push(slot);
+        dispatch();
+    }});
+    slot.worker.addEventListener(\"error\", (e) => retire(slot, new Error(e.message)));
+}}
 
-  }};
-}});
+const call = (method{method_type}, args{args_type}) =>
+    new Promise((resolve, reject) => {{
+        if (alive === 0) return reject(new Error(\"no wasm workers left\"));
+        queue.push({{ id: nextId++, method, args, resolve, reject }});
+        dispatch();
+    }});
+{proxy}
+// one call per worker, spread across the pool
+Promise.all(
+    Array.from({{ length: poolSize }}, (_, i) => wasm.hello_wasm(`{project_name} worker task ${{i}}`)),
+)
+    .then((results) => alert(results.join(\"\\n\")))
+    .catch((error) => console.error(`{project_name} worker pool: ${{error.message}}`));
 "
         )
     }
-}
-
-pub mod jsx {
-    pub fn wasm_worker_jsx(project_name: &str) -> String {
-        format!(
-            "
-let worker = new Worker(\"{project_name}_worker.js\");
 
-await new Promise(r => setTimeout(r, 250)); // let wasm load
+    /// Worker loading the wasm, then answering `{ id, method, args }` with
+    /// the result of the wasm export `method`. Posts `ready` once it can, or
+    /// `error` if loading failed. Module workers import the `--target web`
+    /// glue like the page does, classic ones load the `no-modules` one.
+    ///
+    /// With a `thread_pool`, the worker also starts rayon's threads, one per
+    /// core, before it reports ready.
+    pub fn vanilla_js_wasm_worker_sub(
+        project_name: &str,
+        module: bool,
+        thread_pool: bool,
+    ) -> String {
+        let (load, exports) = if module {
+            let thread_pool = if thread_pool {
+                "\n  .then(() => wasm.initThreadPool(navigator.hardwareConcurrency))"
+            } else {
+                ""
+            };
+            (
+                format!(
+                    "import init, * as wasm from \"./{project_name}_wasm.js\";
 
-worker.onmessage = (e) => {{
-    alert(e.data)
-}}
+init(){thread_pool}"
+                ),
+                "wasm",
+            )
+        } else {
+            (
+                format!(
+                    "importScripts(\"{project_name}_wasm.js\");
 
-worker.postMessage(true);
+wasm_bindgen(\"{project_name}_wasm_bg.wasm\")"
+                ),
+                "wasm_bindgen",
+            )
+        };
+        format!(
+            "
+{load}
+  .then(() => {{
+    onmessage = (e) => {{
+      const {{ id, method, args }} = e.data;
+      try {{
+        if (typeof {exports}[method] !== \"function\") {{
+          throw new Error(`no wasm export named ${{method}}`);
+        }}
+        postMessage({{ type: \"response\", id, result: {exports}[method](...args) }});
+      }} catch (error) {{
+        const message = error instanceof Error ? error.message : String(error);
+        postMessage({{ type: \"response\", id, error: message }});
+      }}
+    }};
+    postMessage({{ type: \"ready\" }});
+  }})
+  .catch((error) => {{
+    postMessage({{ type: \"error\", message: String(error) }});
+  }});
+"
+        )
+    }
 
-function App() {{
-    return(
-        <h1>Hello {project_name} from react</h1>
-    )
-}}
+    fn worker_options(module_glue: Option<&str>) -> &'static str {
+        match module_glue {
+            Some(_) => ", { type: \"module\" }",
+            None => "",
+        }
+    }
 
-ReactDOM.render(<App />, document.querySelector(\"body\"));
+    /// Promise-returning view of every wasm export, for the TypeScript workers.
+    const REMOTE_TYPES: &str = "
+type Pending = { resolve: (value: unknown) => void; reject: (error: Error) => void };
+// every export of the wasm glue, returning a promise of its result
+type Remote<T> = {
+    [K in keyof T]: T[K] extends (...args: infer A) => infer R ? (...args: A) => Promise<R> : never;
+};
+";
 
-        "
+    /// `wasm`, turning method calls into `call(method, args)`.
+    fn wasm_proxy(typescript: bool, module_glue: Option<&str>) -> String {
+        let (args_type, cast) = match (typescript, module_glue) {
+            (false, _) => ("", String::new()),
+            (true, Some(glue)) => (
+                ": unknown[]",
+                format!(" as Remote<typeof import(\"{glue}\")>"),
+            ),
+            (true, None) => (": unknown[]", " as Remote<typeof wasm_bindgen>".to_string()),
+        };
+        format!(
+            "
+// `wasm.hello_wasm(name)` runs `hello_wasm` off the main thread. `then` is
+// left out so awaiting the proxy itself doesn't send a call.
+const wasm = new Proxy(
+    {{}},
+    {{
+        get: (_, method) =>
+            method === \"then\" ? undefined : (...args{args_type}) => call(String(method), args),
+    }},
+){cast};
+"
         )
+    }
+}
 
+pub mod jsx {
+    pub fn react_packages() -> Vec<(&'static str, &'static str)> {
+        vec![("react", "^18.2.0"), ("react-dom", "^18.2.0")]
     }
 
-    pub fn wasm_jsx(project_name: &str) -> String {
-        format!(
-            "
+    /// UMD builds loaded by unbundled pages, as `(node_modules path, pkg path)`
+    /// for offline projects that ship their own copy.
+    pub const REACT_UMD: [(&str, &str); 2] = [
+        (
+            "node_modules/react/umd/react.production.min.js",
+            "pkg/vendor/react.production.min.js",
+        ),
+        (
+            "node_modules/react-dom/umd/react-dom.production.min.js",
+            "pkg/vendor/react-dom.production.min.js",
+        ),
+    ];
 
-import init, {{hello_wasm}} from \"./{project_name}_wasm.js\";
-await init();
+    /// ES modules re-exporting the UMD globals, so unbundled pages can run
+    /// the same imports as bundled ones. The page's import map points
+    /// `react`, `react-dom/client` and `react/jsx-runtime` at them.
+    pub const UMD_MODULES: [(&str, &str); 3] = [
+        (
+            "pkg/react/index.js",
+            "const React = window.React;
+export default React;
+export const {
+    Children, Component, Fragment, PureComponent, StrictMode, Suspense,
+    cloneElement, createContext, createElement, createRef, forwardRef,
+    isValidElement, lazy, memo, startTransition, useCallback, useContext,
+    useDebugValue, useDeferredValue, useEffect, useId, useImperativeHandle,
+    useInsertionEffect, useLayoutEffect, useMemo, useReducer, useRef,
+    useState, useSyncExternalStore, useTransition,
+} = React;
+",
+        ),
+        (
+            "pkg/react/dom-client.js",
+            "export const { createRoot, hydrateRoot } = window.ReactDOM;
+",
+        ),
+        (
+            "pkg/react/jsx-runtime.js",
+            "const { createElement, Fragment } = window.React;
+export { Fragment };
 
-alert(hello_wasm(\"{project_name}\"));
+// props already hold the children, which createElement keeps when it isn't
+// given any separately
+export const jsx = (type, props, key) =>
+    createElement(type, key === undefined ? props : { ...props, key });
+export const jsxs = jsx;
+",
+        ),
+    ];
 
-function App() {{
-    return(
-        <h1>Hello {project_name} from react</h1>
-    )
+    /// With `api`, the component also shows the message from the server
+    /// crate's `/api/hello`.
+    pub fn app(project_name: &str, api: bool, typescript: bool) -> String {
+        if !api {
+            return format!(
+                "export default function App() {{
+    return <h1>Hello {project_name} from react</h1>;
 }}
+"
+            );
+        }
+        let message_type = if typescript { "<string | null>" } else { "" };
+        format!(
+            "import {{ useEffect, useState }} from \"react\";
 
-ReactDOM.render(<App />, document.querySelector(\"body\"));
-
-        "
+export default function App() {{
+    const [message, setMessage] = useState{message_type}(null);
+    useEffect(() => {{
+        fetch(\"/api/hello\")
+            .then((response) => response.json())
+            .then((greeting) => setMessage(greeting.message))
+            .catch((e) => console.error(\"/api/hello failed\", e));
+    }}, []);
+    return (
+        <>
+            <h1>Hello {project_name} from react</h1>
+            {{message && <p>{{message}}</p>}}
+        </>
+    );
+}}
+"
         )
     }
 
-    pub fn vanilla_jsx(project_name: &str) -> String {
+    /// Entry script mounting `App` on the page's `#root` element, with
+    /// `wasm_loader` run before the first render.
+    pub fn main(app_import: &str, wasm_loader: &str, typescript: bool) -> String {
+        let non_null = if typescript { "!" } else { "" };
         format!(
-            "
-
-function App() {{
-    return(
-        <h1>Hello {project_name} from react</h1>
-    )
-}}
-
-ReactDOM.render(<App />, document.querySelector(\"body\"));
-
-        "
+            "import {{ StrictMode }} from \"react\";
+import {{ createRoot }} from \"react-dom/client\";
+import App from \"{app_import}\";
+{wasm_loader}
+createRoot(document.getElementById(\"root\"){non_null}).render(
+    <StrictMode>
+        <App />
+    </StrictMode>,
+);
+"
         )
     }
 }
 
 pub mod babel {
-    pub fn scripts(project_name: &str) -> String {
-        format!("\n\t\"scripts\": {{\n\t\t\"build\": \"babel {project_name}.jsx -d pkg\"\n\t}},\n")
+    /// Transpiles every script in `app/` into `pkg/`.
+    pub fn build_command(typescript: bool) -> String {
+        if typescript {
+            "babel app -d pkg --extensions .ts,.tsx".to_string()
+        } else {
+            "babel app -d pkg".to_string()
+        }
     }
 
-    pub fn scripts_wasm(project_name: &str) -> String {
-        format!(
-            "
-            \"scripts\": {{
-                \"build\": \"babel {project_name}.jsx -d pkg\",
-                \"wasm\": \"wasm-pack build --target web --no-typescript --no-pack\"
-            }},
-            "
-        )
+    pub fn packages(typescript: bool) -> Vec<(&'static str, &'static str)> {
+        let mut packages = vec![
+            ("@babel/cli", "^7.24.0"),
+            ("@babel/core", "^7.24.0"),
+            ("@babel/node", "^7.23.0"),
+            ("@babel/preset-env", "^7.24.0"),
+            ("@babel/preset-react", "^7.24.0"),
+        ];
+        if typescript {
+            packages.push(("@babel/preset-typescript", "^7.24.0"));
+        }
+        packages
     }
 
-    pub fn scripts_wasm_worker(project_name: &str) -> String {
+    pub fn config(typescript: bool) -> String {
+        let typescript_preset = if typescript {
+            ",
+        [
+        \"@babel/preset-typescript\"
+        ]"
+        } else {
+            ""
+        };
         format!(
-            "
-            \"scripts\": {{
-                \"build\": \"babel {project_name}.jsx -d pkg\",
-                \"wasm\": \"wasm-pack build --target no-modules --no-pack --no-typescript\"
-            }},
-            "
-        )
-    }
-
-    pub fn config() -> String {
-        "{
+            "{{
     \"presets\": [
         [
         \"@babel/preset-env\",
-        {
+        {{
             \"modules\": false
-        }
+        }}
         ],
         [
         \"@babel/preset-react\",
-        {
-            \"runtime\": \"classic\"
-        }
-        ]
+        {{
+            \"runtime\": \"automatic\"
+        }}
+        ]{typescript_preset}
     ]
-    }"
-        .to_string()
+    }}"
+        )
+    }
+}
+
+pub mod typescript {
+    pub fn packages(react: bool) -> Vec<(&'static str, &'static str)> {
+        let mut packages = vec![("typescript", "^5.4.0")];
+        if react {
+            packages.extend([("@types/react", "^18.2.0"), ("@types/react-dom", "^18.2.0")]);
+        }
+        packages
+    }
+
+    /// Vanilla sources are compiled by tsc itself, React ones are only type
+    /// checked here and transpiled by babel.
+    pub fn build_command(source: &str) -> String {
+        if source.ends_with(".tsx") {
+            type_checked(&super::babel::build_command(true))
+        } else {
+            "tsc".to_string()
+        }
+    }
+
+    /// Runs `build` once tsc has checked the sources without emitting anything.
+    pub fn type_checked(build: &str) -> String {
+        format!("tsc --noEmit && {build}")
+    }
+
+    /// `rootDirs` lets `./<name>_wasm.js` imports resolve to the declarations
+    /// wasm-pack writes into `pkg/`, matching where the compiled script runs.
+    /// `globals` adds a declaration file that isn't imported, like the one
+    /// for the worker's `wasm_bindgen` global.
+    pub fn tsconfig(source: &str, emit: bool, globals: Option<&str>) -> String {
+        let files = match globals {
+            Some(globals) => format!("\"{source}\", \"{globals}\""),
+            None => format!("\"{source}\""),
+        };
+        let source_dir = match source.rsplit_once('/') {
+            Some((dir, _)) => dir,
+            None => ".",
+        };
+        let output = if source.ends_with(".tsx") {
+            "\"jsx\": \"react-jsx\",
+        \"noEmit\": true"
+        } else if emit {
+            "\"outDir\": \"pkg\""
+        } else {
+            "\"noEmit\": true"
+        };
+        format!(
+            "{{
+    \"compilerOptions\": {{
+        \"target\": \"ES2022\",
+        \"module\": \"ES2022\",
+        \"moduleResolution\": \"bundler\",
+        \"lib\": [\"ES2022\", \"DOM\"],
+        \"strict\": true,
+        \"skipLibCheck\": true,
+        \"rootDirs\": [\"{source_dir}\", \"pkg\"],
+        {output}
+    }},
+    \"files\": [{files}]
+}}
+"
+        )
+    }
+}
+
+pub mod bundler {
+    pub fn vite_packages(react: bool) -> Vec<(&'static str, &'static str)> {
+        let mut packages = vec![("vite", "^5.2.0")];
+        if react {
+            packages.push(("@vitejs/plugin-react", "^4.2.0"));
+        }
+        packages
+    }
+
+    pub fn esbuild_packages() -> Vec<(&'static str, &'static str)> {
+        vec![("esbuild", "^0.20.0")]
+    }
+
+    /// Builds `source` into `pkg/{project_name}.js`, where the unbundled
+    /// builds put it too, so `pkg/` stays a complete static site.
+    pub fn vite_config(project_name: &str, source: &str, react: bool) -> String {
+        let (import, plugins) = if react {
+            ("import react from \"@vitejs/plugin-react\";\n", "react()")
+        } else {
+            ("", "")
+        };
+        format!(
+            "import {{ defineConfig }} from \"vite\";
+{import}
+export default defineConfig({{
+    plugins: [{plugins}],
+    publicDir: false,
+    build: {{
+        outDir: \"pkg\",
+        emptyOutDir: false,
+        target: \"es2022\",
+        rollupOptions: {{
+            input: \"{source}\",
+            output: {{
+                entryFileNames: \"{project_name}.js\",
+                assetFileNames: \"assets/[name]-[hash][extname]\",
+            }},
+        }},
+    }},
+}});
+"
+        )
+    }
+
+    pub fn vite_build() -> String {
+        "vite build".to_string()
+    }
+
+    pub fn vite_dev() -> String {
+        "vite build --watch --mode development".to_string()
+    }
+
+    pub fn esbuild_build(project_name: &str, source: &str) -> String {
+        format!("esbuild {source} --bundle --format=esm --target=es2022 --jsx=automatic --outfile=pkg/{project_name}.js")
+    }
+
+    pub fn esbuild_dev(project_name: &str, source: &str) -> String {
+        format!("{} --watch", esbuild_build(project_name, source))
     }
 }
 
@@ -201,10 +620,65 @@ pub mod html {
         )
     }
 
-    #[allow(unused)]
-    pub fn html_react(project_name: &str) -> String {
+    /// Script the dev server adds to served pages so they reload when it says so.
+    pub fn reload_client(path: &str) -> String {
         format!(
-        "
+            "
+<script>
+(() => {{
+    const connect = (reopened) => {{
+        const socket = new WebSocket(`ws://${{location.host}}{path}`);
+        socket.onopen = () => reopened && location.reload();
+        socket.onmessage = (e) => e.data === \"reload\" && location.reload();
+        socket.onclose = () => setTimeout(() => connect(true), 1000);
+    }};
+    connect(false);
+}})();
+</script>
+"
+        )
+    }
+
+    /// Where a React page gets React from.
+    pub enum React {
+        /// Built into the page script.
+        Bundled,
+        /// UMD builds from unpkg.
+        Unpkg,
+        /// UMD builds copied into `pkg/vendor/` for offline projects.
+        Vendored,
+    }
+
+    /// Page for React, mounting on `#root`. Pages loading the UMD builds map
+    /// the React imports to [`super::jsx::UMD_MODULES`].
+    pub fn html_react(project_name: &str, script: &str, react: React) -> String {
+        let umd_base = match react {
+            React::Bundled => None,
+            React::Vendored => Some(("vendor", "vendor", "")),
+            React::Unpkg => Some((
+                "https://unpkg.com/react@18/umd",
+                "https://unpkg.com/react-dom@18/umd",
+                " crossorigin",
+            )),
+        };
+        let react_scripts = match umd_base {
+            None => String::new(),
+            Some((react, react_dom, crossorigin)) => format!(
+                "<script src=\"{react}/react.production.min.js\" type=\"text/javascript\"{crossorigin}></script>
+    <script src=\"{react_dom}/react-dom.production.min.js\" type=\"text/javascript\"{crossorigin}></script>
+    <script type=\"importmap\">
+    {{
+        \"imports\": {{
+            \"react\": \"./react/index.js\",
+            \"react-dom/client\": \"./react/dom-client.js\",
+            \"react/jsx-runtime\": \"./react/jsx-runtime.js\"
+        }}
+    }}
+    </script>"
+            ),
+        };
+        format!(
+            "
 <!DOCTYPE html>
 <html lang=\"en\">
 
@@ -212,85 +686,499 @@ pub mod html {
     <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0, user-scalable=no\">
     <title>{project_name}</title>
     <link rel=\"stylesheet\" href=\"{project_name}_styles.css\">
-    <script src=\"https://unpkg.com/react@18/umd/react.production.min.js\" type=\"text/javascript\" crossorigin></script>
-    <script src=\"https://unpkg.com/react-dom@18/umd/react-dom.production.min.js\" type=\"text/javascript\" crossorigin></script>
-   
+    {react_scripts}
 </head>
 
 <body>
-    <script type=\"module\" src=\"{project_name}.js\"></script>
+    <div id=\"root\"></div>
+    <script type=\"module\" src=\"{script}\"></script>
 </body>
 
 </html>
 "
-    )
+        )
     }
 }
 
 pub mod rust {
-    pub fn wasm() -> String {
-        "
+    /// In worker projects every `#[wasm_bindgen]` function is also callable
+    /// from the main thread through the generated `wasm` proxy.
+    ///
+    /// With `shared`, the name of the workspace's shared crate, the greeting
+    /// is built from its `Greeting`.
+    pub fn wasm(worker: bool, shared: Option<&str>) -> String {
+        let rpc_note = if worker {
+            "
+// Every #[wasm_bindgen] function here runs in the web worker and is called
+// from the page as `await wasm.<name>(...)`. Errors returned as `JsError`
+// reject that promise.
+"
+        } else {
+            ""
+        };
+        let (shared_import, greeting) = match shared {
+            Some(shared) => (
+                format!("\nuse {}::Greeting;\n", shared.replace('-', "_")),
+                "Greeting::new(\"WASM\", name).message".to_string(),
+            ),
+            None => (
+                String::new(),
+                "format!(\"Hello {name} from WASM\")".to_string(),
+            ),
+        };
+        format!(
+            "
+use wasm_bindgen::prelude::*;
+{shared_import}{rpc_note}
+#[wasm_bindgen]
+pub fn hello_wasm(name: &str) -> String {{
+    {greeting}
+}}
+"
+        )
+    }
+
+    /// Crate for wasm threads: rayon's pool runs on web workers spawned by
+    /// wasm-bindgen-rayon, started from JS through `initThreadPool`.
+    pub fn wasm_threads(shared: Option<&str>) -> String {
+        let (shared_import, greeting) = match shared {
+            Some(shared) => (
+                format!("\nuse {}::Greeting;\n", shared.replace('-', "_")),
+                "let from = format!(\"WASM on {} threads\", rayon::current_num_threads());
+    Greeting::new(&from, name).message",
+            ),
+            None => (
+                String::new(),
+                "format!(\"Hello {name} from WASM on {} threads\", rayon::current_num_threads())",
+            ),
+        };
+        format!(
+            "
+use rayon::prelude::*;
 use wasm_bindgen::prelude::*;
+{shared_import}
+pub use wasm_bindgen_rayon::init_thread_pool;
 
 #[wasm_bindgen]
-pub fn hello_wasm(name: &str) -> String {
-    format!(\"Hello {name} from WASM\")
-}
+pub fn hello_wasm(name: &str) -> String {{
+    {greeting}
+}}
+
+/// Runs on every thread of the pool.
+#[wasm_bindgen]
+pub fn sum_of_squares(numbers: &[i32]) -> i32 {{
+    numbers.par_iter().map(|x| x * x).sum()
+}}
+"
+        )
+    }
+
+    /// `shared` is the workspace's shared crate, next to this one.
+    pub fn toml(threads: bool, shared: Option<&str>) -> String {
+        let threads = if threads {
+            "rayon = \"1.8\"\nwasm-bindgen-rayon = \"1.2\"\n"
+        } else {
+            ""
+        };
+        let shared = match shared {
+            Some(shared) => format!("{shared} = {{ path = \"../shared\" }}\n"),
+            None => String::new(),
+        };
+        format!("wasm-bindgen = \"0.2\"\n{threads}{shared}\n[lib]\ncrate-type = [\"cdylib\"]\n")
+    }
+
+    /// Root `Cargo.toml` when there is no crate to be the root package.
+    pub fn workspace(members: &[&str]) -> String {
+        format!(
+            "[workspace]\nmembers = [{}]\nresolver = \"2\"\n",
+            toml_list(members)
+        )
+    }
+
+    /// Appended to the wasm crate's `Cargo.toml`, making it the workspace root.
+    pub fn workspace_section(members: &[&str]) -> String {
+        format!("\n[workspace]\nmembers = [{}]\n", toml_list(members))
+    }
+
+    fn toml_list(items: &[&str]) -> String {
+        let quoted: Vec<String> = items.iter().map(|item| format!("\"{item}\"")).collect();
+        quoted.join(", ")
+    }
+
+    /// Nightly pinned for wasm threads projects. A macro so doctor can
+    /// `concat!` it into its install hint.
+    #[macro_export]
+    macro_rules! threads_channel {
+        () => {
+            "nightly-2024-08-02"
+        };
+    }
+
+    pub const THREADS_CHANNEL: &str = threads_channel!();
+
+    /// Shared memory needs std rebuilt with atomics, which only nightly can do.
+    pub fn threads_toolchain() -> String {
+        format!(
+            "[toolchain]
+channel = \"{THREADS_CHANNEL}\"
+components = [\"rust-src\"]
+targets = [\"wasm32-unknown-unknown\"]
+"
+        )
+    }
+
+    /// `.cargo/config.toml` with the RUSTFLAGS enabling wasm threads.
+    pub fn threads_cargo_config() -> String {
+        "[target.wasm32-unknown-unknown]
+rustflags = [\"-C\", \"target-feature=+atomics,+bulk-memory,+mutable-globals\"]
+
+[unstable]
+build-std = [\"panic_abort\", \"std\"]
 "
         .to_string()
     }
+}
+
+/// The crate of a workspace project holding the types both the wasm crate
+/// and the server use, built natively and for wasm.
+pub mod shared {
+    pub fn crate_name(project_name: &str) -> String {
+        format!("{project_name}_shared")
+    }
 
     pub fn toml() -> String {
-        "wasm-bindgen = \"0.2\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n".to_string()
+        "serde = { version = \"1\", features = [\"derive\"] }\n".to_string()
     }
+
+    pub fn lib() -> String {
+        "use serde::{Deserialize, Serialize};
+
+/// Returned by the server's `/api/hello` and built by the wasm crate, so both
+/// agree on what a greeting is.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Greeting {
+    pub message: String,
 }
 
-pub mod commands {
+impl Greeting {
+    pub fn new(from: &str, name: &str) -> Greeting {
+        Greeting {
+            message: format!(\"Hello {name} from {from}\"),
+        }
+    }
+}
+"
+        .to_string()
+    }
+}
 
-    pub fn wasm_build_command() -> String {
-        "build command:\nwasm-pack build --target web --no-typescript --no-pack\n".to_string()
+/// The axum crate of full-stack projects, in a Cargo workspace with the wasm
+/// crate.
+pub mod server {
+    pub fn crate_name(project_name: &str) -> String {
+        format!("{project_name}_server")
     }
 
-    pub fn wasm_build_command_no_mod() -> String {
-        "build command:\nwasm-pack build --target no-modules --no-pack --no-typescript\n".to_string()
+    /// With `shared`, the workspace's shared crate provides the API types.
+    pub fn toml(project_name: &str, shared: Option<&str>) -> String {
+        let types = match shared {
+            Some(shared) => format!("{shared} = {{ path = \"../shared\" }}"),
+            None => "serde = { version = \"1\", features = [\"derive\"] }".to_string(),
+        };
+        format!(
+            "[package]
+name = \"{}\"
+version = \"0.1.0\"
+edition = \"2021\"
+
+[dependencies]
+axum = \"0.7\"
+{types}
+tokio = {{ version = \"1\", features = [\"macros\", \"rt-multi-thread\"] }}
+tower-http = {{ version = \"0.5\", features = [\"fs\"] }}
+",
+            crate_name(project_name)
+        )
     }
+
+    /// Serves the `pkg/` of the directory it is run from, which `cargo run`
+    /// in the project is, with the page at `/` and the API under `/api`.
+    pub fn main(project_name: &str, shared: Option<&str>) -> String {
+        let (serde_import, types, greeting) = match shared {
+            Some(shared) => (
+                "",
+                format!("\nuse {}::Greeting;\n", shared.replace('-', "_")),
+                format!("Greeting::new(\"axum\", \"{project_name}\")"),
+            ),
+            None => (
+                "use serde::Serialize;\n",
+                "
+#[derive(Serialize)]
+struct Greeting {
+    message: String,
 }
+"
+                .to_string(),
+                format!(
+                    "Greeting {{
+        message: \"Hello {project_name} from axum\".to_string(),
+    }}"
+                ),
+            ),
+        };
+        format!(
+            "use std::net::SocketAddr;
 
-pub mod readme {
-    pub fn react(project_name: &str) -> String {
+use axum::response::Redirect;
+use axum::routing::get;
+use axum::{{Json, Router}};
+{serde_import}use tower_http::services::ServeDir;
+{types}
+async fn hello() -> Json<Greeting> {{
+    Json({greeting})
+}}
+
+#[tokio::main]
+async fn main() {{
+    let app = Router::new()
+        .route(\"/\", get(|| async {{ Redirect::to(\"/{project_name}.html\") }}))
+        .route(\"/api/hello\", get(hello))
+        .fallback_service(ServeDir::new(\"pkg\"));
+
+    let address = SocketAddr::from(([127, 0, 0, 1], 8080));
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .expect(\"failed to bind 127.0.0.1:8080\");
+    println!(\"Serving pkg/ and /api on http://{{address}}/\");
+    axum::serve(listener, app).await.expect(\"server failed\");
+}}
+"
+        )
+    }
+}
+
+/// Templates for the frameworks writing the whole UI in Rust, built by Trunk
+/// from `index.html` into `pkg/`.
+pub mod rust_ui {
+    pub fn yew_toml() -> String {
+        "yew = { version = \"0.21\", features = [\"csr\"] }\n".to_string()
+    }
+
+    pub fn leptos_toml() -> String {
+        "leptos = { version = \"0.6\", features = [\"csr\"] }\n".to_string()
+    }
+
+    pub fn dioxus_toml() -> String {
+        "dioxus = { version = \"0.5\", features = [\"web\"] }\n".to_string()
+    }
+
+    pub fn yew(project_name: &str) -> String {
         format!(
-            "Build command using NPM:
-        npm run build
-        
-        Alt:
-        babel {project_name}.jsx -d pkg
-        "
+            "use yew::prelude::*;
+
+#[function_component]
+fn App() -> Html {{
+    let count = use_state(|| 0);
+    let onclick = {{
+        let count = count.clone();
+        move |_| count.set(*count + 1)
+    }};
+    html! {{
+        <main>
+            <h1>{{ \"Hello {project_name} from Yew\" }}</h1>
+            <button {{onclick}}>{{ format!(\"Clicked {{}} times\", *count) }}</button>
+        </main>
+    }}
+}}
+
+fn main() {{
+    yew::Renderer::<App>::new().render();
+}}
+"
         )
     }
 
-    pub fn react_wasm(project_name: &str) -> String {
+    pub fn leptos(project_name: &str) -> String {
         format!(
-            "Build commands using NPM:
-        npm run build
-        npm run wasm
-        
-        Alt:
-        babel {project_name}.jsx -d pkg
-        wasm-pack build --target web --no-typescript --no-pack
-        "
+            "use leptos::*;
+
+#[component]
+fn App() -> impl IntoView {{
+    let (count, set_count) = create_signal(0);
+    view! {{
+        <main>
+            <h1>\"Hello {project_name} from Leptos\"</h1>
+            <button on:click=move |_| set_count.update(|count| *count += 1)>
+                \"Clicked \" {{count}} \" times\"
+            </button>
+        </main>
+    }}
+}}
+
+fn main() {{
+    mount_to_body(App);
+}}
+"
         )
     }
 
-    pub fn react_wasm_worker(project_name: &str) -> String {
+    pub fn dioxus(project_name: &str) -> String {
         format!(
-            "Build commands using NPM:
-        npm run build
-        npm run wasm
-        
-        Alt:
-        babel {project_name}.jsx -d pkg
-        wasm-pack build --target no-modules --no-typescript --no-pack
-        "
+            "use dioxus::prelude::*;
+
+#[component]
+fn App() -> Element {{
+    let mut count = use_signal(|| 0);
+    rsx! {{
+        main {{
+            h1 {{ \"Hello {project_name} from Dioxus\" }}
+            button {{ onclick: move |_| count += 1, \"Clicked {{count}} times\" }}
+        }}
+    }}
+}}
+
+fn main() {{
+    launch(App);
+}}
+"
+        )
+    }
+
+    /// Trunk's entry point. `body` is the element the app mounts on, if it
+    /// doesn't render straight into `<body>`.
+    pub fn index_html(project_name: &str, body: &str) -> String {
+        format!(
+            "<!DOCTYPE html>
+<html lang=\"en\">
+
+<head>
+    <meta charset=\"utf-8\">
+    <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0, user-scalable=no\">
+    <title>{project_name}</title>
+    <link data-trunk rel=\"css\" href=\"{project_name}_styles.css\">
+    <link data-trunk rel=\"rust\">
+</head>
+
+<body>{body}</body>
+
+</html>
+"
+        )
+    }
+
+    /// Builds into `pkg/`, where every other project type ends up too.
+    pub fn trunk_toml() -> String {
+        "[build]\ntarget = \"index.html\"\ndist = \"pkg\"\n".to_string()
+    }
+}
+
+pub mod commands {
+    /// Every supported package manager runs scripts with `run`.
+    pub fn build_args() -> Vec<&'static str> {
+        vec!["run", "build"]
+    }
+
+    /// TypeScript projects keep the `.d.ts` files wasm-pack generates. Offline
+    /// builds use the installed wasm-bindgen and cargo's local registry cache.
+    ///
+    /// A wasm crate in [`crate::spec::WASM_CRATE_DIR`] is built from the
+    /// project root, into its `pkg/`.
+    pub fn wasm_pack_args(
+        target: &str,
+        typescript: bool,
+        offline: bool,
+        workspace: bool,
+    ) -> Vec<&str> {
+        let mut args = vec!["build"];
+        if workspace {
+            // --out-dir is relative to the crate
+            args.extend([crate::spec::WASM_CRATE_DIR, "--out-dir", "../../pkg"]);
+        }
+        args.extend(["--target", target]);
+        if !typescript {
+            args.push("--no-typescript");
+        }
+        if offline {
+            args.extend(["--mode", "no-install"]);
+        }
+        args.push("--no-pack");
+        if offline {
+            args.extend(["--", "--offline"]);
+        }
+        args
+    }
+
+    /// Builds the server crate at generation time so a broken template or
+    /// missing crate shows up then.
+    pub fn server_build_args(crate_name: &str, offline: bool) -> Vec<&str> {
+        let mut args = vec!["build", "-p", crate_name];
+        if offline {
+            args.push("--offline");
+        }
+        args
+    }
+
+    pub fn trunk_args(offline: bool) -> Vec<&'static str> {
+        let mut args = vec!["build", "--release"];
+        if offline {
+            args.push("--offline");
+        }
+        args
+    }
+
+    pub fn trunk_build(offline: bool) -> String {
+        format!("trunk {}", trunk_args(offline).join(" "))
+    }
+
+    pub fn wasm_build(target: &str, typescript: bool, offline: bool, workspace: bool) -> String {
+        format!(
+            "wasm-pack {}",
+            wasm_pack_args(target, typescript, offline, workspace).join(" ")
         )
     }
 }
+
+pub mod readme {
+    pub fn dev(package_manager: &str, command: &str) -> String {
+        format!("Rebuild on change:\n        {package_manager} run dev\n        \n        Alt:\n        {command}\n        \n")
+    }
+
+    /// Readme listing each build step as a package.json script, with the raw
+    /// command as an alternative.
+    pub fn scripts(package_manager: &str, lockfile: &str, steps: &[(&str, String)]) -> String {
+        let scripts: Vec<String> = steps
+            .iter()
+            .map(|(name, _)| format!("        {package_manager} run {name}\n"))
+            .collect();
+        let commands: Vec<String> = steps
+            .iter()
+            .map(|(_, command)| format!("        {command}\n"))
+            .collect();
+        format!(
+            "Install dependencies:\n        {package_manager} install\n        \n\
+             Commit {lockfile} so everyone installs the same versions.\n        \n\
+             Build commands using {package_manager}:\n{}        \n        Alt:\n{}        \n{}",
+            scripts.concat(),
+            commands.concat(),
+            serve()
+        )
+    }
+
+    pub fn plain(steps: &[(&str, String)]) -> String {
+        let commands: Vec<String> = steps
+            .iter()
+            .map(|(_, command)| format!("{command}\n"))
+            .collect();
+        format!("build command:\n{}\n{}", commands.concat(), serve())
+    }
+
+    pub fn server(crate_name: &str) -> String {
+        format!(
+            "\nServe pkg/ and the API on http://127.0.0.1:8080, from this directory:\n        cargo run -p {crate_name}\n"
+        )
+    }
+
+    fn serve() -> String {
+        "Serve pkg/ on http://127.0.0.1:8080:\n        init_web_app serve\n".to_string()
+    }
+}
@@ -0,0 +1,25 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Catches Ctrl-C so a run can stop at the next step and clean up after
+/// itself instead of dying with a half-written project on disk.
+pub fn install_handler() {
+    #[cfg(unix)]
+    {
+        const SIGINT: i32 = 2;
+        extern "C" {
+            fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+        }
+        extern "C" fn on_sigint(_: i32) {
+            INTERRUPTED.store(true, Ordering::SeqCst);
+        }
+        unsafe {
+            signal(SIGINT, on_sigint);
+        }
+    }
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,177 @@
+use crate::json::Value;
+
+/// The parts of package.json this tool owns. Everything else in an existing
+/// file is left as it was.
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    /// Sets `"type": "module"`, so config files like vite.config.js load as ES modules.
+    pub module: bool,
+    pub scripts: Vec<(String, String)>,
+    pub dependencies: Vec<(&'static str, &'static str)>,
+    pub dev_dependencies: Vec<(&'static str, &'static str)>,
+}
+
+impl Package {
+    pub fn new(project_name: &str) -> Package {
+        Package {
+            name: npm_name(project_name),
+            version: "0.1.0".to_string(),
+            module: true,
+            scripts: Vec::new(),
+            dependencies: Vec::new(),
+            dev_dependencies: Vec::new(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        self.merge_into(Value::Object(Vec::new()))
+            .map(|json| json.to_pretty())
+            .unwrap_or_default()
+    }
+
+    /// Adds this package's fields to an existing package.json. `name` and
+    /// `version` are only filled in when missing, scripts and dependencies
+    /// replace entries with the same key and keep the rest.
+    pub fn merge(&self, existing: &str) -> Result<String, String> {
+        let json = Value::parse(existing)?;
+        self.merge_into(json).map(|json| json.to_pretty())
+    }
+
+    fn merge_into(&self, mut json: Value) -> Result<Value, String> {
+        if !matches!(json, Value::Object(_)) {
+            return Err("expected an object".to_string());
+        }
+        for (key, value) in [("name", &self.name), ("version", &self.version)] {
+            if json.get(key).is_none() {
+                json.set(key, Value::String(value.clone()));
+            }
+        }
+        if json.get("private").is_none() {
+            json.set("private", Value::Bool(true));
+        }
+        if self.module {
+            json.set("type", Value::String("module".to_string()));
+        }
+
+        let scripts = self.scripts.iter().map(|(k, v)| (k.as_str(), v.as_str()));
+        merge_entries(&mut json, "scripts", scripts)?;
+        merge_entries(&mut json, "dependencies", self.dependencies.iter().copied())?;
+        merge_entries(
+            &mut json,
+            "devDependencies",
+            self.dev_dependencies.iter().copied(),
+        )?;
+        Ok(json)
+    }
+}
+
+fn merge_entries<'a>(
+    json: &mut Value,
+    key: &str,
+    entries: impl ExactSizeIterator<Item = (&'a str, &'a str)>,
+) -> Result<(), String> {
+    if entries.len() == 0 {
+        return Ok(());
+    }
+    let mut object = match json.get(key) {
+        Some(object @ Value::Object(_)) => object.clone(),
+        Some(_) => return Err(format!("expected \"{key}\" to be an object")),
+        None => Value::Object(Vec::new()),
+    };
+    for (name, value) in entries {
+        object.set(name, Value::String(value.to_string()));
+    }
+    json.set(key, object);
+    Ok(())
+}
+
+/// npm only accepts lowercase names made of URL safe characters.
+fn npm_name(project_name: &str) -> String {
+    project_name
+        .chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            c @ ('a'..='z' | '0'..='9' | '-' | '_' | '.') => c,
+            _ => '-',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package() -> Package {
+        let mut package = Package::new("My App");
+        package.scripts = vec![("build".to_string(), "babel app -d pkg".to_string())];
+        package.dev_dependencies = vec![("@babel/cli", "^7.23.0")];
+        package
+    }
+
+    #[test]
+    fn new_package_json() {
+        let json = Value::parse(&package().to_json()).unwrap();
+        assert_eq!(json.get("name"), Some(&Value::String("my-app".to_string())));
+        assert_eq!(json.get("private"), Some(&Value::Bool(true)));
+        assert_eq!(json.get("type"), Some(&Value::String("module".to_string())));
+        assert_eq!(json.get("dependencies"), None);
+    }
+
+    #[test]
+    fn merge_keeps_existing_keys_and_replaces_scripts() {
+        let existing = r#"{
+  "name": "kept",
+  "version": "2.0.0",
+  "private": false,
+  "license": "MIT",
+  "scripts": {
+    "build": "old build",
+    "lint": "eslint ."
+  },
+  "devDependencies": {
+    "eslint": "^8.0.0"
+  }
+}
+"#;
+        let json = Value::parse(&package().merge(existing).unwrap()).unwrap();
+        assert_eq!(json.get("name"), Some(&Value::String("kept".to_string())));
+        assert_eq!(
+            json.get("version"),
+            Some(&Value::String("2.0.0".to_string()))
+        );
+        assert_eq!(json.get("private"), Some(&Value::Bool(false)));
+        assert_eq!(json.get("license"), Some(&Value::String("MIT".to_string())));
+        assert_eq!(json.get("type"), Some(&Value::String("module".to_string())));
+
+        let scripts = json.get("scripts").unwrap();
+        assert_eq!(
+            scripts.get("build"),
+            Some(&Value::String("babel app -d pkg".to_string()))
+        );
+        assert_eq!(
+            scripts.get("lint"),
+            Some(&Value::String("eslint .".to_string()))
+        );
+        let dev = json.get("devDependencies").unwrap();
+        assert_eq!(
+            dev.get("eslint"),
+            Some(&Value::String("^8.0.0".to_string()))
+        );
+        assert_eq!(
+            dev.get("@babel/cli"),
+            Some(&Value::String("^7.23.0".to_string()))
+        );
+    }
+
+    #[test]
+    fn merge_rejects_non_object_scripts() {
+        let error = package().merge(r#"{"scripts": ["build"]}"#).unwrap_err();
+        assert_eq!(error, "expected \"scripts\" to be an object");
+    }
+
+    #[test]
+    fn merge_rejects_invalid_json() {
+        assert!(package().merge("[]").is_err());
+        assert!(package().merge("{\"name\": }").is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+
+use crate::error::InitError;
+
+/// Reads answers to prompts from the terminal, piped stdin or an answers file.
+///
+/// Only a terminal is treated as interactive; everything else is read as a
+/// list of answers, one per line, and running out of lines is an error.
+pub struct Prompter {
+    source: Box<dyn BufRead>,
+    interactive: bool,
+}
+
+impl Prompter {
+    pub fn stdin() -> Prompter {
+        let stdin = std::io::stdin();
+        Prompter {
+            interactive: stdin.is_terminal(),
+            source: Box::new(stdin.lock()),
+        }
+    }
+
+    pub fn answers_file(path: &str) -> Result<Prompter, InitError> {
+        match File::open(path) {
+            Ok(file) => Ok(Prompter {
+                source: Box::new(BufReader::new(file)),
+                interactive: false,
+            }),
+            Err(e) => Err(InitError::io("open answers file", path)(e)),
+        }
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    /// Prints `prompt` and returns the next non-empty line of input.
+    pub fn get_string(&mut self, prompt: &str) -> Result<String, InitError> {
+        println!("\n{prompt}\n");
+        let _ = std::io::stdout().flush();
+        loop {
+            let mut line = String::new();
+            match self.source.read_line(&mut line) {
+                Ok(0) => {
+                    return Err(InitError::Input(format!(
+                        "Input closed before an answer to: {prompt}"
+                    )))
+                }
+                Ok(_) => {
+                    let line = line.trim();
+                    if !line.is_empty() {
+                        return Ok(line.to_string());
+                    }
+                }
+                Err(e) => return Err(InitError::io("read", "stdin")(e)),
+            }
+        }
+    }
+
+    /// Shows `title` with a numbered list of `choices` and returns the picked value.
+    ///
+    /// Interactive input is asked again until it names a valid choice; any
+    /// other source fails on the first invalid answer.
+    pub fn select<T: Copy>(&mut self, title: &str, choices: &[(&str, T)]) -> Result<T, InitError> {
+        let mut prompt = format!("{title}:");
+        for (i, (label, _)) in choices.iter().enumerate() {
+            prompt.push_str(&format!("\n    \t{}) {label}", i + 1));
+        }
+
+        loop {
+            let answer = self.get_string(&prompt)?;
+            match answer.parse::<usize>() {
+                Ok(i) if (1..=choices.len()).contains(&i) => return Ok(choices[i - 1].1),
+                _ if self.interactive => continue,
+                _ => {
+                    return Err(InitError::Input(format!(
+                        "Invalid answer {answer} to: {title}"
+                    )))
+                }
+            }
+        }
+    }
+}
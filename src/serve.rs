@@ -0,0 +1,304 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::error::InitError;
+use crate::helpers;
+use crate::spec::{ProjectSpec, WasmMode};
+use crate::watch;
+use crate::websocket;
+
+/// Path the injected live reload client opens its WebSocket on.
+const RELOAD_PATH: &str = "/__reload";
+
+struct Server {
+    root: PathBuf,
+    live_reload: bool,
+    /// Sends the COOP/COEP headers that unlock `SharedArrayBuffer`, which
+    /// wasm threads are built on.
+    cross_origin_isolated: bool,
+    /// Open live reload connections, told to reload when `root` changes.
+    clients: Mutex<Vec<TcpStream>>,
+}
+
+/// Serves `project_dir/pkg` on localhost until the process is stopped. With
+/// `live_reload`, html pages get a client that reloads them when anything
+/// under `pkg/` changes.
+pub fn serve(project_dir: &str, port: u16, live_reload: bool) -> Result<(), InitError> {
+    let root = Path::new(project_dir).join("pkg");
+    if !root.is_dir() {
+        return Err(InitError::Usage(format!(
+            "{project_dir} has no pkg directory to serve."
+        )));
+    }
+
+    // only projects this tool generated have a manifest
+    let cross_origin_isolated =
+        ProjectSpec::load(project_dir).is_ok_and(|(_, spec)| spec.wasm == WasmMode::Threads);
+
+    let address = format!("127.0.0.1:{port}");
+    let listener = TcpListener::bind(&address).map_err(InitError::io("listen on", &address))?;
+    match index_page(&root) {
+        Some(index) => println!("Serving {} at http://{address}/{index}", root.display()),
+        None => println!("Serving {} at http://{address}/", root.display()),
+    }
+    if cross_origin_isolated {
+        println!("Cross-origin isolated for wasm threads.");
+    }
+
+    let server = Arc::new(Server {
+        root,
+        live_reload,
+        cross_origin_isolated,
+        clients: Mutex::new(Vec::new()),
+    });
+    if live_reload {
+        let server = server.clone();
+        std::thread::spawn(move || watch_for_reload(&server));
+    }
+
+    for stream in listener.incoming().flatten() {
+        let server = server.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = handle(stream, &server) {
+                eprintln!("serve: {e}");
+            }
+        });
+    }
+    Ok(())
+}
+
+fn watch_for_reload(server: &Server) {
+    let pkg_files = || {
+        let mut files = Vec::new();
+        watch::collect_files(&server.root, None, &mut files);
+        files
+    };
+    let mut last_change = watch::last_change(&pkg_files());
+    loop {
+        std::thread::sleep(Duration::from_millis(300));
+        let change = watch::last_change(&pkg_files());
+        if change == last_change {
+            continue;
+        }
+        // builds write several files, let them finish first
+        std::thread::sleep(Duration::from_millis(300));
+        last_change = watch::last_change(&pkg_files());
+
+        let mut clients = server.clients.lock().unwrap_or_else(|e| e.into_inner());
+        println!("pkg changed, reloading {} page(s)", clients.len());
+        clients.retain_mut(|client| websocket::send_text(client, "reload").is_ok());
+    }
+}
+
+fn handle(mut stream: TcpStream, server: &Server) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut websocket_key = None;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("sec-websocket-key") {
+                websocket_key = Some(value.trim().to_string());
+            }
+        }
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("/");
+    if method != "GET" && method != "HEAD" {
+        return respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            "",
+            b"",
+            false,
+        );
+    }
+
+    let head = method == "HEAD";
+    if target == RELOAD_PATH && server.live_reload {
+        let Some(key) = websocket_key else {
+            return respond(&mut stream, "400 Bad Request", "text/plain", "", b"", head);
+        };
+        websocket::accept(&mut stream, &key)?;
+        let mut clients = server.clients.lock().unwrap_or_else(|e| e.into_inner());
+        clients.push(stream);
+        return Ok(());
+    }
+
+    let file = match resolve(&server.root, target) {
+        Some(file) => file,
+        None => return not_found(&mut stream, head),
+    };
+    let mut body = match std::fs::read(&file) {
+        Ok(ok) => ok,
+        Err(_) => return not_found(&mut stream, head),
+    };
+    println!("{method} {target}");
+    let content_type = mime_type(&file);
+    if server.live_reload && content_type.starts_with("text/html") {
+        body = inject_reload_client(body);
+    }
+    let headers = if server.cross_origin_isolated {
+        "Cross-Origin-Opener-Policy: same-origin\r\nCross-Origin-Embedder-Policy: require-corp\r\n"
+    } else {
+        ""
+    };
+    respond(&mut stream, "200 OK", content_type, headers, &body, head)
+}
+
+fn not_found(stream: &mut TcpStream, head: bool) -> std::io::Result<()> {
+    respond(
+        stream,
+        "404 Not Found",
+        "text/plain",
+        "",
+        b"Not Found",
+        head,
+    )
+}
+
+/// Adds the live reload client just before `</body>`, or at the end of pages
+/// without one.
+fn inject_reload_client(html: Vec<u8>) -> Vec<u8> {
+    let html = String::from_utf8_lossy(&html);
+    let client = helpers::html::reload_client(RELOAD_PATH);
+    match html.rfind("</body>") {
+        Some(i) => format!("{}{client}{}", &html[..i], &html[i..]),
+        None => format!("{html}{client}"),
+    }
+    .into_bytes()
+}
+
+/// `headers` are extra header lines, each ending in `\r\n`. Responses to
+/// `head` requests describe `body` without sending it.
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    headers: &str,
+    body: &[u8],
+    head: bool,
+) -> std::io::Result<()> {
+    let mut response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\n{headers}Connection: close\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    if !head {
+        response.extend_from_slice(body);
+    }
+    stream.write_all(&response)
+}
+
+/// Maps a request target onto a file under `root`, refusing anything that
+/// would escape it.
+fn resolve(root: &Path, target: &str) -> Option<PathBuf> {
+    let path = percent_decode(target.split(['?', '#']).next().unwrap_or(""))?;
+    let mut file = root.to_path_buf();
+    for component in Path::new(path.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(part) => file.push(part),
+            Component::CurDir => (),
+            _ => return None,
+        }
+    }
+    if file.is_dir() {
+        file.push(index_page(&file)?);
+    }
+    file.is_file().then_some(file)
+}
+
+/// Decodes every `%XX` in a request path, failing on malformed escapes and
+/// on anything that isn't UTF-8 once decoded.
+fn percent_decode(path: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// `index.html` if there is one, otherwise the only html file in `dir`, which
+/// is what every generated project has.
+fn index_page(dir: &Path) -> Option<String> {
+    if dir.join("index.html").is_file() {
+        return Some("index.html".to_string());
+    }
+    let mut pages = std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".html"));
+    match (pages.next(), pages.next()) {
+        (Some(page), None) => Some(page),
+        _ => None,
+    }
+}
+
+fn mime_type(file: &Path) -> &'static str {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js" | "mjs") => "text/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("wasm") => "application/wasm",
+        Some("json" | "map") => "application/json",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("ico") => "image/x-icon",
+        Some("txt") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_percent_escapes() {
+        assert_eq!(
+            percent_decode("/a%20b%2Bc.js").as_deref(),
+            Some("/a b+c.js")
+        );
+        assert_eq!(percent_decode("/caf%C3%A9").as_deref(), Some("/café"));
+        for malformed in ["/%", "/%2", "/%zz", "/%+1", "/%FF"] {
+            assert_eq!(percent_decode(malformed), None, "{malformed}");
+        }
+    }
+
+    #[test]
+    fn resolve_stays_under_root() {
+        let root = std::env::temp_dir().join(format!("init_web_app_serve_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("a b&c.js"), "").unwrap();
+
+        assert_eq!(
+            resolve(&root, "/a%20b%26c.js?v=1"),
+            Some(root.join("a b&c.js"))
+        );
+        assert_eq!(resolve(&root, "/../etc/passwd"), None);
+        assert_eq!(resolve(&root, "/%2e%2e/etc/passwd"), None);
+        assert_eq!(resolve(&root, "/%2e%2e%2fetc%2fpasswd"), None);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
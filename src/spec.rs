@@ -0,0 +1,502 @@
+use crate::error::InitError;
+
+/// UI layer the generated project is written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Framework {
+    Vanilla,
+    React,
+    /// The frameworks below write the whole UI in Rust and build it with Trunk.
+    Yew,
+    Leptos,
+    Dioxus,
+}
+
+/// Whether a Rust crate is generated and where its wasm output is loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WasmMode {
+    Disabled,
+    MainThread,
+    Worker,
+    /// Several workers sharing a queue of calls.
+    WorkerPool,
+    /// A worker running shared-memory wasm threads through rayon.
+    Threads,
+}
+
+/// Language the hand-written frontend sources are in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    JavaScript,
+    TypeScript,
+}
+
+/// Bundler building the entry script into `pkg/` in place of babel or tsc.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bundler {
+    Vite,
+    Esbuild,
+}
+
+/// Tool installing the project's npm packages and running its scripts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+/// Everything that decides what gets generated. Each field is an independent
+/// axis; the init pipeline adds the pieces for each one in turn.
+#[derive(Clone, Debug)]
+pub struct ProjectSpec {
+    pub framework: Framework,
+    pub wasm: WasmMode,
+    pub language: Language,
+    pub bundler: Option<Bundler>,
+    pub package_manager: PackageManager,
+    /// Generate without network access: React is copied into `pkg/` instead
+    /// of loaded from a CDN, and installs and wasm builds use local caches.
+    pub offline: bool,
+    /// npm registry written to the project's `.npmrc`, such as a local mirror.
+    pub registry: Option<String>,
+    /// Size of a [`WasmMode::WorkerPool`]; one worker per core when unset.
+    pub workers: Option<u32>,
+    /// Run workers as ES modules loading the same `--target web` glue as the
+    /// page, instead of classic workers on the `no-modules` one.
+    pub module_workers: bool,
+    /// Add an axum crate in `server/` serving `pkg/` and a JSON API the
+    /// frontend fetches, making the project a Cargo workspace.
+    pub server: bool,
+    /// Lay the Rust crates out as a Cargo workspace under `crates/`, with the
+    /// types the wasm crate and server share in a crate of their own.
+    pub workspace: bool,
+}
+
+impl Framework {
+    pub const CHOICES: [(&'static str, Framework); 5] = [
+        ("vanilla js", Framework::Vanilla),
+        ("react", Framework::React),
+        ("yew (rust)", Framework::Yew),
+        ("leptos (rust)", Framework::Leptos),
+        ("dioxus (rust)", Framework::Dioxus),
+    ];
+
+    pub fn from_slug(slug: &str) -> Result<Framework, InitError> {
+        match slug {
+            "vanilla" | "vanilla-js" => Ok(Framework::Vanilla),
+            "react" => Ok(Framework::React),
+            "yew" => Ok(Framework::Yew),
+            "leptos" => Ok(Framework::Leptos),
+            "dioxus" => Ok(Framework::Dioxus),
+            _ => Err(InitError::Usage(format!("Unknown framework {slug}."))),
+        }
+    }
+
+    pub fn slug(self) -> &'static str {
+        match self {
+            Framework::Vanilla => "vanilla",
+            Framework::React => "react",
+            Framework::Yew => "yew",
+            Framework::Leptos => "leptos",
+            Framework::Dioxus => "dioxus",
+        }
+    }
+
+    /// Whether the whole UI is a Rust crate, leaving no JavaScript to write
+    /// and no separate wasm mode to choose.
+    pub fn is_rust_ui(self) -> bool {
+        matches!(self, Framework::Yew | Framework::Leptos | Framework::Dioxus)
+    }
+}
+
+impl WasmMode {
+    pub const CHOICES: [(&'static str, WasmMode); 5] = [
+        ("no wasm", WasmMode::Disabled),
+        ("wasm", WasmMode::MainThread),
+        ("wasm worker", WasmMode::Worker),
+        ("wasm worker pool", WasmMode::WorkerPool),
+        ("wasm threads (rayon)", WasmMode::Threads),
+    ];
+
+    pub fn from_slug(slug: &str) -> Result<WasmMode, InitError> {
+        match slug {
+            "none" => Ok(WasmMode::Disabled),
+            "main" => Ok(WasmMode::MainThread),
+            "worker" => Ok(WasmMode::Worker),
+            "pool" => Ok(WasmMode::WorkerPool),
+            "threads" => Ok(WasmMode::Threads),
+            _ => Err(InitError::Usage(format!("Unknown wasm mode {slug}."))),
+        }
+    }
+
+    pub fn slug(self) -> &'static str {
+        match self {
+            WasmMode::Disabled => "none",
+            WasmMode::MainThread => "main",
+            WasmMode::Worker => "worker",
+            WasmMode::WorkerPool => "pool",
+            WasmMode::Threads => "threads",
+        }
+    }
+
+    /// Whether the wasm runs in web workers rather than on the page.
+    pub fn in_worker(self) -> bool {
+        matches!(
+            self,
+            WasmMode::Worker | WasmMode::WorkerPool | WasmMode::Threads
+        )
+    }
+}
+
+impl Language {
+    pub const CHOICES: [(&'static str, Language); 2] = [
+        ("javascript", Language::JavaScript),
+        ("typescript", Language::TypeScript),
+    ];
+
+    pub fn from_slug(slug: &str) -> Result<Language, InitError> {
+        match slug {
+            "js" | "javascript" => Ok(Language::JavaScript),
+            "ts" | "typescript" => Ok(Language::TypeScript),
+            _ => Err(InitError::Usage(format!("Unknown language {slug}."))),
+        }
+    }
+
+    pub fn slug(self) -> &'static str {
+        match self {
+            Language::JavaScript => "js",
+            Language::TypeScript => "ts",
+        }
+    }
+}
+
+impl Bundler {
+    pub const CHOICES: [(&'static str, Option<Bundler>); 3] = [
+        ("none", None),
+        ("vite", Some(Bundler::Vite)),
+        ("esbuild", Some(Bundler::Esbuild)),
+    ];
+
+    pub fn from_slug(slug: &str) -> Result<Option<Bundler>, InitError> {
+        match slug {
+            "none" => Ok(None),
+            "vite" => Ok(Some(Bundler::Vite)),
+            "esbuild" => Ok(Some(Bundler::Esbuild)),
+            _ => Err(InitError::Usage(format!("Unknown bundler {slug}."))),
+        }
+    }
+
+    pub fn slug(bundler: Option<Bundler>) -> &'static str {
+        match bundler {
+            None => "none",
+            Some(Bundler::Vite) => "vite",
+            Some(Bundler::Esbuild) => "esbuild",
+        }
+    }
+}
+
+impl PackageManager {
+    pub const ALL: [PackageManager; 4] = [
+        PackageManager::Npm,
+        PackageManager::Pnpm,
+        PackageManager::Yarn,
+        PackageManager::Bun,
+    ];
+
+    pub fn from_slug(slug: &str) -> Result<PackageManager, InitError> {
+        match slug {
+            "npm" => Ok(PackageManager::Npm),
+            "pnpm" => Ok(PackageManager::Pnpm),
+            "yarn" => Ok(PackageManager::Yarn),
+            "bun" => Ok(PackageManager::Bun),
+            _ => Err(InitError::Usage(format!("Unknown package manager {slug}."))),
+        }
+    }
+
+    /// Also the name of the program to run.
+    pub fn slug(self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Yarn => "yarn",
+            PackageManager::Bun => "bun",
+        }
+    }
+
+    /// `install` restricted to packages already in the local cache. bun has
+    /// no such switch and installs from its global cache when it can. Only
+    /// Yarn 1 has `--offline`, see [`PackageManager::supports_offline`].
+    pub fn offline_install_args(self) -> Vec<&'static str> {
+        match self {
+            PackageManager::Bun => vec!["install"],
+            _ => vec!["install", "--offline"],
+        }
+    }
+
+    /// Whether `version`, the installed `(major, minor)` if known, can
+    /// install offline. Yarn 2 and later dropped `install --offline`.
+    pub fn supports_offline(self, version: Option<(u32, u32)>) -> bool {
+        match (self, version) {
+            (PackageManager::Yarn, Some((major, _))) => major < 2,
+            _ => true,
+        }
+    }
+
+    /// File and contents pointing `install` of `version`, the installed
+    /// `(major, minor)` if known, at `registry`. Yarn 2 and later only read
+    /// `.yarnrc.yml`.
+    pub fn registry_config(
+        self,
+        version: Option<(u32, u32)>,
+        registry: &str,
+    ) -> (&'static str, String) {
+        match (self, version) {
+            (PackageManager::Yarn, Some((major, _))) if major >= 2 => (
+                ".yarnrc.yml",
+                format!("npmRegistryServer: \"{registry}\"\n"),
+            ),
+            _ => (".npmrc", format!("registry={registry}\n")),
+        }
+    }
+
+    /// Lockfile written by `install` of `version`, the installed
+    /// `(major, minor)` if known, to be committed with the project. Bun
+    /// writes a text `bun.lock` since 1.2, the binary `bun.lockb` before.
+    pub fn lockfile(self, version: Option<(u32, u32)>) -> &'static str {
+        match self {
+            PackageManager::Npm => "package-lock.json",
+            PackageManager::Pnpm => "pnpm-lock.yaml",
+            PackageManager::Yarn => "yarn.lock",
+            PackageManager::Bun if version.is_some_and(|version| version < (1, 2)) => "bun.lockb",
+            PackageManager::Bun => "bun.lock",
+        }
+    }
+}
+
+/// Directory holding the React sources, kept apart from the Rust crate's `src/`.
+pub const APP_DIR: &str = "app";
+
+/// Crates of a [`ProjectSpec::workspace`] project.
+pub const WASM_CRATE_DIR: &str = "crates/wasm";
+pub const SHARED_CRATE_DIR: &str = "crates/shared";
+pub const SERVER_CRATE_DIR: &str = "crates/server";
+
+/// File recording how a project was generated, read back by `watch`.
+pub const MANIFEST: &str = "init_web_app.toml";
+
+impl ProjectSpec {
+    /// Looks up one of the named combinations accepted by `--type`.
+    pub fn from_preset(slug: &str) -> Result<ProjectSpec, InitError> {
+        let (framework, wasm) = match slug {
+            "vanilla-js" => (Framework::Vanilla, WasmMode::Disabled),
+            "vanilla-js-wasm" => (Framework::Vanilla, WasmMode::MainThread),
+            "vanilla-js-wasm-worker" => (Framework::Vanilla, WasmMode::Worker),
+            "react" => (Framework::React, WasmMode::Disabled),
+            "react-wasm" => (Framework::React, WasmMode::MainThread),
+            "react-wasm-worker" => (Framework::React, WasmMode::Worker),
+            _ => return Err(InitError::Usage(format!("Unknown project type {slug}."))),
+        };
+        Ok(ProjectSpec {
+            framework,
+            wasm,
+            language: Language::JavaScript,
+            bundler: None,
+            package_manager: PackageManager::Npm,
+            offline: false,
+            registry: None,
+            workers: None,
+            module_workers: false,
+            server: false,
+            workspace: false,
+        })
+    }
+
+    /// Rejects combinations of options that can't be generated together.
+    pub fn validate(&self) -> Result<(), InitError> {
+        let error = if self.workers.is_some() && self.wasm != WasmMode::WorkerPool {
+            "--workers only applies to --wasm pool."
+        } else if self.module_workers && !self.wasm.in_worker() {
+            "--module-workers only applies to --wasm worker or pool."
+        } else if self.server && self.wasm == WasmMode::Threads {
+            // build-std in .cargo/config.toml would apply to the server too
+            "--server can't share a workspace with --wasm threads."
+        } else if self.workspace && self.wasm == WasmMode::Disabled {
+            "--workspace needs a wasm crate; pick a --wasm mode other than none."
+        } else {
+            return Ok(());
+        };
+        Err(InitError::Usage(error.to_string()))
+    }
+
+    /// Path of the hand-written entry script, relative to the project. Only
+    /// unbundled vanilla JavaScript is written straight into `pkg/`; the rest
+    /// is built into it. React sources live in [`APP_DIR`].
+    pub fn source_file(&self, project_name: &str) -> String {
+        match (self.framework, self.language) {
+            (Framework::Vanilla, Language::JavaScript) if self.bundler.is_none() => {
+                format!("pkg/{project_name}.js")
+            }
+            (Framework::Vanilla, Language::JavaScript) => format!("{project_name}.js"),
+            (Framework::Vanilla, Language::TypeScript) => format!("{project_name}.ts"),
+            (Framework::React, Language::JavaScript) => format!("{APP_DIR}/main.jsx"),
+            (Framework::React, Language::TypeScript) => format!("{APP_DIR}/main.tsx"),
+            (_, _) => "src/main.rs".to_string(),
+        }
+    }
+
+    /// The root component imported by a React entry script.
+    pub fn app_file(&self) -> Option<String> {
+        match (self.framework, self.language) {
+            (Framework::React, Language::JavaScript) => Some(format!("{APP_DIR}/App.jsx")),
+            (Framework::React, Language::TypeScript) => Some(format!("{APP_DIR}/App.tsx")),
+            _ => None,
+        }
+    }
+
+    /// Script the page loads from `pkg/`. Babel keeps the names of the
+    /// files it compiles, bundlers are told to write `{project_name}.js`.
+    pub fn page_script(&self, project_name: &str) -> String {
+        match (self.framework, self.bundler) {
+            (Framework::React, None) => "main.js".to_string(),
+            _ => format!("{project_name}.js"),
+        }
+    }
+
+    /// Whether the entry script needs a compiler installed from npm packages.
+    pub fn uses_npm(&self) -> bool {
+        self.framework == Framework::React
+            || self.language == Language::TypeScript
+            || self.bundler.is_some()
+    }
+
+    /// The wasm-pack `--target` the crate is built with, if there is one.
+    /// Trunk builds the Rust UIs instead.
+    pub fn wasm_target(&self) -> Option<&'static str> {
+        match self.wasm {
+            WasmMode::Disabled => None,
+            _ if self.framework.is_rust_ui() => None,
+            WasmMode::Worker | WasmMode::WorkerPool if !self.module_workers => Some("no-modules"),
+            _ => Some("web"),
+        }
+    }
+
+    /// Threads need the `--target web` glue in their worker, so they always
+    /// get a module worker.
+    pub fn uses_module_workers(&self) -> bool {
+        self.module_workers || self.wasm == WasmMode::Threads
+    }
+
+    /// Import path of the wasm-pack glue as seen from the entry script.
+    /// Unbundled scripts run from `pkg/` next to it, bundled ones are
+    /// resolved from where the source is.
+    pub fn wasm_import_dir(&self) -> &'static str {
+        match (self.framework, self.bundler) {
+            (_, None) => ".",
+            (Framework::React, Some(_)) => "../pkg",
+            (_, Some(_)) => "./pkg",
+        }
+    }
+
+    /// Directory of the wasm crate, the project root unless it is a workspace.
+    pub fn wasm_crate_dir(&self) -> &'static str {
+        if self.workspace {
+            WASM_CRATE_DIR
+        } else {
+            "."
+        }
+    }
+
+    pub fn server_crate_dir(&self) -> &'static str {
+        if self.workspace {
+            SERVER_CRATE_DIR
+        } else {
+            "server"
+        }
+    }
+
+    /// Contents of the [`MANIFEST`] written into a generated project.
+    pub fn to_manifest(&self, project_name: &str) -> String {
+        format!(
+            "# generated by init_web_app, read by `init_web_app watch`\n\
+             name = \"{project_name}\"\n\
+             framework = \"{}\"\n\
+             wasm = \"{}\"\n\
+             language = \"{}\"\n\
+             bundler = \"{}\"\n\
+             package_manager = \"{}\"\n\
+             offline = {}\n\
+             module_workers = {}\n\
+             server = {}\n\
+             workspace = {}\n",
+            self.framework.slug(),
+            self.wasm.slug(),
+            self.language.slug(),
+            Bundler::slug(self.bundler),
+            self.package_manager.slug(),
+            self.offline,
+            self.module_workers,
+            self.server,
+            self.workspace
+        )
+    }
+
+    /// Reads the [`MANIFEST`] of the project in `project_dir`, returning the
+    /// project name and spec it was generated with.
+    pub fn load(project_dir: &str) -> Result<(String, ProjectSpec), InitError> {
+        let path = format!("{project_dir}/{MANIFEST}");
+        let manifest = std::fs::read_to_string(&path).map_err(InitError::io("read", &path))?;
+
+        let mut name = None;
+        let mut framework = None;
+        let mut wasm = None;
+        let mut language = Language::JavaScript;
+        let mut bundler = None;
+        let mut package_manager = PackageManager::Npm;
+        let mut offline = false;
+        let mut module_workers = false;
+        let mut server = false;
+        let mut workspace = false;
+        for line in manifest.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('"');
+            match key.trim() {
+                "name" => name = Some(value.to_string()),
+                "framework" => framework = Some(Framework::from_slug(value)?),
+                "wasm" => wasm = Some(WasmMode::from_slug(value)?),
+                "language" => language = Language::from_slug(value)?,
+                "bundler" => bundler = Bundler::from_slug(value)?,
+                "package_manager" => package_manager = PackageManager::from_slug(value)?,
+                "offline" => offline = value == "true",
+                "module_workers" => module_workers = value == "true",
+                "server" => server = value == "true",
+                "workspace" => workspace = value == "true",
+                _ => (),
+            }
+        }
+
+        match (name, framework, wasm) {
+            (Some(name), Some(framework), Some(wasm)) => Ok((
+                name,
+                ProjectSpec {
+                    framework,
+                    wasm,
+                    language,
+                    bundler,
+                    package_manager,
+                    offline,
+                    registry: None,
+                    workers: None,
+                    module_workers,
+                    server,
+                    workspace,
+                },
+            )),
+            _ => Err(InitError::Usage(format!(
+                "{path} is missing name, framework or wasm."
+            ))),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use crate::error::InitError;
+use crate::helpers;
+use crate::spec::{self, Language, ProjectSpec};
+
+/// A set of source files and the build that has to rerun when one changes.
+struct Watched {
+    label: &'static str,
+    /// Lists the watched files given the project directory and entry script.
+    sources: fn(&Path, &str) -> Vec<PathBuf>,
+    program: &'static str,
+    args: Vec<&'static str>,
+    last_change: Option<SystemTime>,
+}
+
+/// Polls the sources of the project in `project_dir` and reruns the script or
+/// wasm-pack build it was generated with whenever they change.
+pub fn watch(project_dir: &str) -> Result<(), InitError> {
+    let (project_name, spec) = ProjectSpec::load(project_dir)?;
+
+    let mut watched = Vec::new();
+    let source = spec.source_file(&project_name);
+    if spec.uses_npm() {
+        watched.push(Watched {
+            label: "Script",
+            sources: script_sources,
+            program: spec.package_manager.slug(),
+            args: helpers::commands::build_args(),
+            last_change: None,
+        });
+    }
+    if spec.framework.is_rust_ui() {
+        watched.push(Watched {
+            label: "Rust",
+            sources: rust_ui_sources,
+            program: "trunk",
+            args: helpers::commands::trunk_args(spec.offline),
+            last_change: None,
+        });
+    }
+    if let Some(target) = spec.wasm_target() {
+        watched.push(Watched {
+            label: "Rust",
+            sources: if spec.workspace {
+                workspace_rust_sources
+            } else {
+                rust_sources
+            },
+            program: "wasm-pack",
+            args: helpers::commands::wasm_pack_args(
+                target,
+                spec.language == Language::TypeScript,
+                spec.offline,
+                spec.workspace,
+            ),
+            last_change: None,
+        });
+    }
+    if watched.is_empty() {
+        return Err(InitError::Usage(format!(
+            "{project_dir} has no JSX, TypeScript or Rust sources to build."
+        )));
+    }
+
+    let dir = Path::new(project_dir);
+    for w in &mut watched {
+        w.last_change = last_change(&(w.sources)(dir, &source));
+        println!("Watching {} sources.", w.label);
+    }
+    loop {
+        for w in &mut watched {
+            let change = last_change(&(w.sources)(dir, &source));
+            if change == w.last_change {
+                continue;
+            }
+            w.last_change = change;
+            let command = format!("{} {}", w.program, w.args.join(" "));
+            println!("{} changed, running {command}", w.label);
+            match Command::new(w.program)
+                .args(&w.args)
+                .current_dir(dir)
+                .status()
+            {
+                Ok(status) if status.success() => println!("Rebuilt {}.", w.label),
+                Ok(status) => eprintln!("`{command}` failed ({status})."),
+                Err(e) => eprintln!("Failed to run `{command}`: {e}"),
+            }
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+pub fn last_change(files: &[PathBuf]) -> Option<SystemTime> {
+    files
+        .iter()
+        .filter_map(|file| file.metadata().and_then(|meta| meta.modified()).ok())
+        .max()
+}
+
+fn script_sources(dir: &Path, source: &str) -> Vec<PathBuf> {
+    if source.starts_with(&format!("{}/", spec::APP_DIR)) {
+        let mut files = Vec::new();
+        collect_files(&dir.join(spec::APP_DIR), None, &mut files);
+        files
+    } else {
+        vec![dir.join(source)]
+    }
+}
+
+fn rust_sources(dir: &Path, _: &str) -> Vec<PathBuf> {
+    let mut files = vec![dir.join("Cargo.toml")];
+    collect_files(&dir.join("src"), Some("rs"), &mut files);
+    files
+}
+
+/// The crates compiled into the wasm, leaving out the server.
+fn workspace_rust_sources(dir: &Path, _: &str) -> Vec<PathBuf> {
+    let mut files = vec![dir.join("Cargo.toml")];
+    for crate_dir in [spec::WASM_CRATE_DIR, spec::SHARED_CRATE_DIR] {
+        files.push(dir.join(crate_dir).join("Cargo.toml"));
+        collect_files(&dir.join(crate_dir).join("src"), Some("rs"), &mut files);
+    }
+    files
+}
+
+/// Trunk also rebuilds for the page and the stylesheets next to it; the
+/// hashed copies it writes to pkg/ are left out.
+fn rust_ui_sources(dir: &Path, source: &str) -> Vec<PathBuf> {
+    let mut files = rust_sources(dir, source);
+    files.push(dir.join("index.html"));
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        files.extend(
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "css")),
+        );
+    }
+    files
+}
+
+/// Adds every file under `dir` to `files`, or only those ending in `.{extension}`.
+pub fn collect_files(dir: &Path, extension: Option<&str>, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            collect_files(&path, extension, files);
+        } else if extension
+            .is_none_or(|extension| path.extension().is_some_and(|ext| ext == extension))
+        {
+            files.push(path);
+        }
+    }
+}